@@ -0,0 +1,70 @@
+use crate::components::{Attributes, Skills};
+
+pub const BASE_ATTRIBUTE: i32 = 10;
+pub const BASE_ARMOR_CLASS: i32 = 10;
+
+/// Every two points above (or below) the baseline of 10 is worth +1 (or -1).
+pub fn attribute_bonus(value: i32) -> i32 {
+    (value - BASE_ATTRIBUTE).div_euclid(2)
+}
+
+pub fn player_max_hp(attributes: &Attributes, level: i32) -> i32 {
+    BASE_ATTRIBUTE
+        + attributes.fitness
+        + level * i32::max(1, 5 + attribute_bonus(attributes.fitness))
+}
+
+pub fn max_mana(attributes: &Attributes, skills: &Skills, level: i32) -> i32 {
+    level
+        * i32::max(
+            0,
+            2 + attribute_bonus(attributes.intelligence) + skills.magic * 2,
+        )
+}
+
+/// Turns between each point of mana regained.
+pub fn mana_regen_turns(attributes: &Attributes, skills: &Skills) -> i32 {
+    i32::max(
+        2,
+        8 - attribute_bonus(attributes.intelligence) - skills.magic,
+    )
+}
+
+pub fn carry_capacity(attributes: &Attributes) -> i32 {
+    attributes.might * 15
+}
+
+pub fn melee_to_hit(attributes: &Attributes, skills: &Skills) -> i32 {
+    attribute_bonus(attributes.might) + skills.melee
+}
+
+pub fn ranged_to_hit(attributes: &Attributes) -> i32 {
+    attribute_bonus(attributes.quickness)
+}
+
+/// Added to every roll to notice a hidden trap.
+pub fn trap_detection(attributes: &Attributes, skills: &Skills) -> i32 {
+    attribute_bonus(attributes.intelligence) + skills.traps
+}
+
+pub fn disarm_bonus(attributes: &Attributes, skills: &Skills) -> i32 {
+    attribute_bonus(attributes.quickness) + skills.traps * 2
+}
+
+/// Makes footsteps quieter and the player harder to spot.
+pub fn stealth_bonus(attributes: &Attributes, skills: &Skills) -> i32 {
+    attribute_bonus(attributes.quickness) + skills.stealth * 2
+}
+
+pub fn melee_damage_bonus(attributes: &Attributes) -> i32 {
+    attribute_bonus(attributes.might)
+}
+
+pub fn armor_class(attributes: &Attributes, skills: &Skills) -> i32 {
+    BASE_ARMOR_CLASS + attribute_bonus(attributes.quickness) + skills.defense
+}
+
+/// Experience needed to advance from `level` to the next; the curve grows with each level.
+pub fn xp_to_next_level(level: i32) -> i32 {
+    50 * level * (level + 1)
+}
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::{
+    components::{InBackpack, QuestItem},
+    spawner,
+};
+
+/// Every this many levels the dungeon gives way to a boss lair.
+pub const BOSS_LEVEL_INTERVAL: i32 = 5;
+/// The bottom of the dungeon, where the amulet is kept.
+pub const FINAL_DEPTH: i32 = 10;
+
+pub fn is_boss_level(depth: i32) -> bool {
+    depth % BOSS_LEVEL_INTERVAL == 0
+}
+
+pub fn boss_for_depth(depth: i32) -> &'static str {
+    if depth >= FINAL_DEPTH {
+        "Morgrath the Warlord"
+    } else {
+        "Grubnak the Goblin King"
+    }
+}
+
+/// Names of the uniques killed so far this run; they never come back.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SlainUniques {
+    names: HashSet<String>,
+}
+
+impl SlainUniques {
+    pub fn new() -> SlainUniques {
+        SlainUniques::default()
+    }
+
+    pub fn record(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+    }
+
+    pub fn is_slain(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+}
+
+/// Places the level's boss at (x, y), unless it has already been killed this run.
+pub fn spawn_boss(world: &mut World, x: i32, y: i32, depth: i32) {
+    let name = boss_for_depth(depth);
+    if world.fetch::<SlainUniques>().is_slain(name) {
+        return;
+    }
+    spawner::spawn_entity(world, name, x, y, depth);
+}
+
+pub fn carries_quest_item(world: &World, owner: Entity) -> bool {
+    let backpack = world.read_storage::<InBackpack>();
+    let quest_items = world.read_storage::<QuestItem>();
+    (&backpack, &quest_items)
+        .join()
+        .any(|(pack, _quest)| pack.owner == owner)
+}
@@ -0,0 +1,183 @@
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{Attributes, CombatStats, KnownSpells, Mana, Name, Skills},
+};
+
+pub const MIN_ATTRIBUTE: i32 = 8;
+pub const MAX_ATTRIBUTE: i32 = 15;
+pub const ATTRIBUTE_POINTS: i32 = 12;
+pub const MAX_NAME_LENGTH: usize = 16;
+
+pub const ATTRIBUTE_NAMES: [&str; 4] = ["Might", "Fitness", "Quickness", "Intelligence"];
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum Background {
+    Warrior,
+    Rogue,
+    Mage,
+}
+
+pub const BACKGROUNDS: [Background; 3] = [Background::Warrior, Background::Rogue, Background::Mage];
+
+impl Background {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Background::Warrior => "Warrior",
+            Background::Rogue => "Rogue",
+            Background::Mage => "Mage",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Background::Warrior => "Trained with blades. +2 melee, +1 defense.",
+            Background::Rogue => "Quick on their feet. +1 melee, +2 defense, +2 traps, +2 stealth.",
+            Background::Mage => "Schooled in the arcane. +3 magic.",
+        }
+    }
+
+    pub fn skills(&self) -> Skills {
+        match self {
+            Background::Warrior => Skills {
+                melee: 2,
+                defense: 1,
+                magic: 0,
+                traps: 0,
+                stealth: 0,
+            },
+            Background::Rogue => Skills {
+                melee: 1,
+                defense: 2,
+                magic: 0,
+                traps: 2,
+                stealth: 2,
+            },
+            Background::Mage => Skills {
+                melee: 0,
+                defense: 0,
+                magic: 3,
+                traps: 0,
+                stealth: 0,
+            },
+        }
+    }
+}
+
+/// The character being put together on the creation screens, kept as a resource
+/// until the player confirms it.
+pub struct CharacterBuilder {
+    pub name: String,
+    pub background: Background,
+    pub attributes: Attributes,
+}
+
+impl CharacterBuilder {
+    pub fn new() -> CharacterBuilder {
+        CharacterBuilder {
+            name: String::new(),
+            background: Background::Warrior,
+            attributes: Attributes {
+                might: MIN_ATTRIBUTE,
+                fitness: MIN_ATTRIBUTE,
+                quickness: MIN_ATTRIBUTE,
+                intelligence: MIN_ATTRIBUTE,
+            },
+        }
+    }
+
+    pub fn attribute(&self, index: usize) -> i32 {
+        match index {
+            0 => self.attributes.might,
+            1 => self.attributes.fitness,
+            2 => self.attributes.quickness,
+            _ => self.attributes.intelligence,
+        }
+    }
+
+    fn attribute_mut(&mut self, index: usize) -> &mut i32 {
+        match index {
+            0 => &mut self.attributes.might,
+            1 => &mut self.attributes.fitness,
+            2 => &mut self.attributes.quickness,
+            _ => &mut self.attributes.intelligence,
+        }
+    }
+
+    pub fn points_remaining(&self) -> i32 {
+        ATTRIBUTE_POINTS
+            - (0..ATTRIBUTE_NAMES.len())
+                .map(|index| self.attribute(index) - MIN_ATTRIBUTE)
+                .sum::<i32>()
+    }
+
+    pub fn adjust_attribute(&mut self, index: usize, delta: i32) {
+        if delta > 0 && self.points_remaining() < delta {
+            return;
+        }
+        let value = self.attribute_mut(index);
+        *value = (*value + delta).clamp(MIN_ATTRIBUTE, MAX_ATTRIBUTE);
+    }
+
+    pub fn push_letter(&mut self, letter: char) {
+        if self.name.len() < MAX_NAME_LENGTH {
+            if self.name.is_empty() {
+                self.name.push(letter.to_ascii_uppercase());
+            } else {
+                self.name.push(letter);
+            }
+        }
+    }
+
+    pub fn finish(&self, world: &mut World) {
+        let player_entity = *world.fetch::<Entity>();
+        let skills = self.background.skills();
+        let max_hp = attributes::player_max_hp(&self.attributes, 1);
+        let max_mana = attributes::max_mana(&self.attributes, &skills, 1);
+
+        world
+            .write_storage::<Name>()
+            .insert(
+                player_entity,
+                Name {
+                    name: self.name.clone(),
+                },
+            )
+            .expect("Unable to name player");
+        world
+            .write_storage::<CombatStats>()
+            .insert(player_entity, CombatStats { max_hp, hp: max_hp })
+            .expect("Unable to insert stats");
+        world
+            .write_storage::<Attributes>()
+            .insert(player_entity, self.attributes.clone())
+            .expect("Unable to insert attributes");
+        world
+            .write_storage::<Skills>()
+            .insert(player_entity, skills)
+            .expect("Unable to insert skills");
+        world
+            .write_storage::<Mana>()
+            .insert(
+                player_entity,
+                Mana {
+                    max: max_mana,
+                    current: max_mana,
+                    regen_timer: 0,
+                },
+            )
+            .expect("Unable to insert mana");
+        if self.background == Background::Mage {
+            world
+                .write_storage::<KnownSpells>()
+                .insert(
+                    player_entity,
+                    KnownSpells {
+                        spells: vec!["Magic Missile".to_string()],
+                    },
+                )
+                .expect("Unable to insert known spells");
+        }
+    }
+}
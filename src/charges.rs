@@ -0,0 +1,74 @@
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::{
+    components::{Charges, DamageType, Name, Stackable},
+    effects::{EffectType, EffectsQueue, Targets},
+    gamelog::GameLog,
+    identification::ItemNameTable,
+};
+
+/// Percent chance that a recharge overloads the item and blows it apart.
+const EXPLODE_CHANCE: i32 = 20;
+
+/// Reading a recharge scroll on `target`: refills its charges, or destroys it and burns the reader.
+pub fn recharge_with_scroll(world: &mut World, scroll: Entity, target: Entity) {
+    let player_entity = *world.fetch::<Entity>();
+    let target_name = world
+        .read_storage::<Name>()
+        .get(target)
+        .unwrap()
+        .name
+        .clone();
+    let scroll_name = world
+        .read_storage::<Name>()
+        .get(scroll)
+        .unwrap()
+        .name
+        .clone();
+    world
+        .write_resource::<ItemNameTable>()
+        .identify(&scroll_name);
+    let explodes = world
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 100)
+        <= EXPLODE_CHANCE;
+
+    if explodes {
+        let damage = world
+            .write_resource::<RandomNumberGenerator>()
+            .roll_dice(2, 6);
+        world.write_resource::<EffectsQueue>().add_effect(
+            None,
+            EffectType::Damage {
+                amount: damage,
+                damage_type: DamageType::Magic,
+            },
+            Targets::Single {
+                target: player_entity,
+            },
+        );
+        world.write_resource::<GameLog>().entries.push(format!(
+            "The {} overloads and explodes, dealing {} damage!",
+            target_name, damage
+        ));
+        world.delete_entity(target).expect("Delete failed");
+    } else {
+        if let Some(charges) = world.write_storage::<Charges>().get_mut(target) {
+            charges.current = charges.max;
+        }
+        world
+            .write_resource::<GameLog>()
+            .entries
+            .push(format!("The {} hums with renewed power.", target_name));
+    }
+
+    let mut stackables = world.write_storage::<Stackable>();
+    match stackables.get_mut(scroll) {
+        Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+        _ => {
+            drop(stackables);
+            world.delete_entity(scroll).expect("Delete failed");
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use rltk::{FontCharType, RGB};
+use rltk::{DiceType, FontCharType, RGB};
 use serde::{Deserialize, Serialize};
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{ConvertSaveload, Marker};
 use specs_derive::*;
 
-use crate::map::Map;
+use crate::{bosses::SlainUniques, identification::ItemNameTable, map::Map, noise::ALERT_TURNS};
 
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Position {
@@ -46,8 +46,69 @@ pub struct BlocksTile {}
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Attributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes {
+            might: 10,
+            fitness: 10,
+            quickness: 10,
+            intelligence: 10,
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Skills {
+    pub melee: i32,
     pub defense: i32,
-    pub power: i32,
+    pub magic: i32,
+    pub traps: i32,
+    pub stealth: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct ExperienceValue {
+    pub xp: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Mana {
+    pub max: i32,
+    pub current: i32,
+    pub regen_timer: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct KnownSpells {
+    pub spells: Vec<String>,
+}
+
+/// Spells are unpositioned entities carrying the same effect components as scrolls.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct SpellTemplate {
+    pub mana_cost: i32,
+}
+
+#[derive(Component, ConvertSaveload, Debug)]
+pub struct WantsToCastSpell {
+    pub spell: Entity,
+    pub target: Option<rltk::Point>,
 }
 
 #[derive(Component, ConvertSaveload, Debug)]
@@ -55,13 +116,98 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, ConvertSaveload, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Bludgeoning,
+    Fire,
+    Cold,
+    Poison,
+    Magic,
+    Hunger,
+}
+
+#[derive(Component, Clone, Debug)]
 pub struct SuffersDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<(i32, DamageType)>,
+    pub last_hit_by: Option<Entity>,
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum ResistanceLevel {
+    Immune,
+    Resistant,
+    Vulnerable,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Resistances {
+    pub resistances: Vec<(DamageType, ResistanceLevel)>,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
-pub struct Item {}
+pub struct Item {
+    pub category: ItemCategory,
+    pub weight: f32,
+    pub base_value: i32,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum ItemCategory {
+    Weapon,
+    Armor,
+    Jewelry,
+    Potion,
+    Scroll,
+    Wand,
+    Food,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Overburdened {}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Wallet {
+    pub gold: i32,
+}
+
+/// A pile of coins on the floor, added straight to the wallet when picked up.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Gold {
+    pub amount: i32,
+}
+
+/// A unique monster guarding a milestone level. It keeps hunting once it has seen the player and
+/// flies into a rage when badly hurt.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Boss {
+    pub aware: bool,
+    pub enraged: bool,
+}
+
+/// Carrying this out of the dungeon wins the game.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct QuestItem {}
+
+/// Casts light around itself, or around whoever is carrying it.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LightSource {
+    pub colour: RGB,
+    pub range: i32,
+}
+
+/// Names the drop table rolled when this monster dies.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct LootTable {
+    pub table: String,
+}
+
+/// Trades in these categories; its stock is whatever sits in its own backpack.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Vendor {
+    pub categories: Vec<ItemCategory>,
+}
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct ProvidesHealing {
@@ -84,6 +230,12 @@ pub struct WantsToDropItem {
     pub item: Entity,
 }
 
+#[derive(Component, ConvertSaveload, Debug)]
+pub struct WantsToThrowItem {
+    pub item: Entity,
+    pub target: rltk::Point,
+}
+
 #[derive(Component, ConvertSaveload, Debug)]
 pub struct WantsToUseItem {
     pub item: Entity,
@@ -93,6 +245,17 @@ pub struct WantsToUseItem {
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Consumable {}
 
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Charges {
+    pub current: i32,
+    pub max: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct Ranged {
     pub range: i32,
@@ -101,6 +264,7 @@ pub struct Ranged {
 #[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, ConvertSaveload, Clone, Debug)]
@@ -108,8 +272,39 @@ pub struct AreaOfEffect {
     pub radius: i32,
 }
 
-#[derive(Component, ConvertSaveload, Clone, Debug)]
-pub struct Confusion {
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum StatusEffectType {
+    Poison,
+    Slow,
+    Haste,
+    Paralysis,
+    Regeneration,
+    Blindness,
+    Confusion,
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum StatusStacking {
+    Refresh,
+    Extend,
+    Intensify,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatusEffect {
+    pub effect: StatusEffectType,
+    pub turns: i32,
+    pub magnitude: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct InflictsStatus {
+    pub effect: StatusEffectType,
     pub turns: i32,
 }
 
@@ -117,22 +312,64 @@ pub struct Confusion {
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ranged,
+    Head,
+    Torso,
+    Legs,
+    Feet,
+    Hands,
+    Amulet,
+    LeftRing,
+    RightRing,
 }
 
+/// Rings are declared for the left hand and slide onto the right one when the left is taken.
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable {
     pub slot: EquipmentSlot,
 }
 
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct TwoHanded {}
+
+/// Weapons that deal their melee damage when thrown instead of just bouncing off.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Throwable {}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum MagicItemClass {
+    Common,
+    Magic,
+    Rare,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct MagicItem {
+    pub class: MagicItemClass,
+    pub enchantment: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Cursed {}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Equipped {
     pub owner: Entity,
     pub slot: EquipmentSlot,
 }
 
-#[derive(Component, ConvertSaveload, Clone)]
-pub struct MeleePowerBonus {
-    pub power: i32,
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MeleeWeapon {
+    pub damage: DiceType,
+    pub hit_bonus: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct NaturalAttack {
+    pub damage: DiceType,
+    pub hit_bonus: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -164,12 +401,35 @@ pub struct HungerClock {
     pub duration: i32,
 }
 
+/// Counts the player's turns on the current level; wandering monsters grow likelier as it rises.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct LevelTimer {
+    pub turns_on_level: i32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct ProvidesFood {}
 
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct MagicMapper {}
 
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Teleports {}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Identifies {}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Recharges {}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct RemovesCurse {}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct TeachesSpell {
+    pub spell: String,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct Hidden {}
 
@@ -182,22 +442,146 @@ pub struct EntityMoved {}
 #[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct SingleActivation {}
 
+/// A trap that alerts every monster within earshot.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Alarm {}
+
+/// A trap that drops whoever steps on it to the level below.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct TrapDoor {}
+
+/// The monster heard or saw something at (x, y) and is on its way to look. It loses interest
+/// once it gets there, or after `turns` turns, unless it catches sight of the player.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Alerted {
+    pub x: i32,
+    pub y: i32,
+    pub turns: i32,
+}
+
+/// The monster is dozing and will not act until a loud enough noise wakes it.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Asleep {}
+
 pub struct SerializeMe;
 
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: Map,
+    pub item_names: ItemNameTable,
+    pub slain_uniques: SlainUniques,
+}
+
+impl Alerted {
+    pub fn at(x: i32, y: i32) -> Alerted {
+        Alerted {
+            x,
+            y,
+            turns: ALERT_TURNS,
+        }
+    }
 }
 
 impl SuffersDamage {
-    pub fn new_damage(store: &mut WriteStorage<SuffersDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SuffersDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+        from: Option<Entity>,
+    ) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type));
+            if from.is_some() {
+                suffering.last_hit_by = from;
+            }
         } else {
             let dmg = SuffersDamage {
-                amount: vec![amount],
+                amount: vec![(amount, damage_type)],
+                last_hit_by: from,
             };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
 }
+
+impl StatusEffectType {
+    pub fn stacking(self) -> StatusStacking {
+        match self {
+            StatusEffectType::Poison => StatusStacking::Intensify,
+            StatusEffectType::Confusion | StatusEffectType::Blindness => StatusStacking::Extend,
+            StatusEffectType::Slow
+            | StatusEffectType::Haste
+            | StatusEffectType::Paralysis
+            | StatusEffectType::Regeneration => StatusStacking::Refresh,
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn new_status(
+        store: &mut WriteStorage<StatusEffects>,
+        victim: Entity,
+        effect: StatusEffectType,
+        turns: i32,
+    ) {
+        if let Some(statuses) = store.get_mut(victim) {
+            statuses.add(effect, turns);
+        } else {
+            let mut statuses = StatusEffects::default();
+            statuses.add(effect, turns);
+            store
+                .insert(victim, statuses)
+                .expect("Unable to insert status");
+        }
+    }
+
+    pub fn add(&mut self, effect: StatusEffectType, turns: i32) {
+        match self
+            .effects
+            .iter_mut()
+            .find(|status| status.effect == effect)
+        {
+            None => self.effects.push(StatusEffect {
+                effect,
+                turns,
+                magnitude: 1,
+            }),
+            Some(status) => match effect.stacking() {
+                StatusStacking::Refresh => status.turns = i32::max(status.turns, turns),
+                StatusStacking::Extend => status.turns += turns,
+                StatusStacking::Intensify => {
+                    status.turns = i32::max(status.turns, turns);
+                    status.magnitude += 1;
+                }
+            },
+        }
+    }
+
+    pub fn get(&self, effect: StatusEffectType) -> Option<&StatusEffect> {
+        self.effects.iter().find(|status| status.effect == effect)
+    }
+
+    pub fn has(&self, effect: StatusEffectType) -> bool {
+        self.get(effect).is_some()
+    }
+}
+
+impl ResistanceLevel {
+    pub fn multiplier(self) -> f32 {
+        match self {
+            ResistanceLevel::Immune => 0.0,
+            ResistanceLevel::Resistant => 0.5,
+            ResistanceLevel::Vulnerable => 2.0,
+        }
+    }
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> Option<ResistanceLevel> {
+        self.resistances
+            .iter()
+            .find(|(resisted, _)| *resisted == damage_type)
+            .map(|(_, level)| *level)
+    }
+}
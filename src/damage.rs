@@ -1,30 +1,76 @@
+use std::ops::Deref;
+
 use crate::{
-    components::{CombatStats, Name, Player, Position, SuffersDamage},
+    bosses::SlainUniques,
+    components::{
+        Boss, CombatStats, DamageType, Equipped, Experience, ExperienceValue, InBackpack,
+        LootTable, Name, Player, Position, ResistanceLevel, Resistances, SuffersDamage,
+    },
     gamelog::GameLog,
     map::Map,
+    spawner,
     state::RunState,
 };
-use specs::prelude::*;
+use rltk::RandomNumberGenerator;
+use specs::{prelude::*, storage::MaskedStorage};
 
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SuffersDamage>,
-        ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Experience>,
+        ReadStorage<'a, ExperienceValue>,
+        WriteExpect<'a, GameLog>,
         Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats,
+            mut damage,
+            resistances,
+            equipped,
+            names,
+            mut experience,
+            experience_values,
+            mut log,
+            entities,
+        ) = data;
+
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            let was_alive = stats.hp > 0;
+
+            for (amount, damage_type) in damage.amount.iter() {
+                let multiplier =
+                    resistance_multiplier(entity, *damage_type, &resistances, &equipped);
+                stats.hp -= (*amount as f32 * multiplier) as i32;
 
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
-            if let Some(pos) = positions.get(entity) {
-                let idx = map.xy_idx(pos.x, pos.y);
-                map.bloodstains.insert(idx);
+                if let (Some(qualifier), Some(name)) = (qualifier(multiplier), names.get(entity)) {
+                    log.entries.push(format!(
+                        "The {} {} the {} ({})",
+                        damage_name(*damage_type),
+                        damage_verb(*damage_type),
+                        name.name,
+                        qualifier
+                    ));
+                }
+            }
+
+            if was_alive && stats.hp < 1 {
+                let killer = damage
+                    .last_hit_by
+                    .and_then(|killer| experience.get_mut(killer));
+                if let (Some(killer), Some(value)) = (killer, experience_values.get(entity)) {
+                    killer.xp += value.xp;
+                    log.entries
+                        .push(format!("You gain {} experience.", value.xp));
+                }
             }
         }
 
@@ -32,6 +78,86 @@ impl<'a> System<'a> for DamageSystem {
     }
 }
 
+/// How much of a hit of `damage_type` actually lands on `target`, counting its own
+/// resistances and those of everything it has equipped.
+pub fn resistance_multiplier<D: Deref<Target = MaskedStorage<Equipped>>>(
+    target: Entity,
+    damage_type: DamageType,
+    resistances: &ReadStorage<Resistances>,
+    equipped: &Storage<Equipped, D>,
+) -> f32 {
+    let mut levels: Vec<ResistanceLevel> = Vec::new();
+    if let Some(level) = resistances.get(target).and_then(|r| r.get(damage_type)) {
+        levels.push(level);
+    }
+    for (equipped_by, item_resistances) in (equipped, resistances).join() {
+        if equipped_by.owner == target {
+            if let Some(level) = item_resistances.get(damage_type) {
+                levels.push(level);
+            }
+        }
+    }
+    damage_multiplier(&levels)
+}
+
+/// The hit points actually lost to a hit, as `DamageSystem` will apply it.
+pub fn resolved_damage<D: Deref<Target = MaskedStorage<Equipped>>>(
+    target: Entity,
+    amount: i32,
+    damage_type: DamageType,
+    resistances: &ReadStorage<Resistances>,
+    equipped: &Storage<Equipped, D>,
+) -> i32 {
+    (amount as f32 * resistance_multiplier(target, damage_type, resistances, equipped)) as i32
+}
+
+/// Immunity always wins; otherwise resistances and vulnerabilities from every source
+/// multiply together, so a resistant creature wearing vulnerable armor takes normal damage.
+fn damage_multiplier(levels: &[ResistanceLevel]) -> f32 {
+    if levels.contains(&ResistanceLevel::Immune) {
+        return 0.0;
+    }
+    levels.iter().map(|level| level.multiplier()).product()
+}
+
+fn qualifier(multiplier: f32) -> Option<&'static str> {
+    if multiplier == 0.0 {
+        Some("immune")
+    } else if multiplier < 1.0 {
+        Some("resisted")
+    } else if multiplier > 1.0 {
+        Some("vulnerable")
+    } else {
+        None
+    }
+}
+
+fn damage_name(damage_type: DamageType) -> &'static str {
+    match damage_type {
+        DamageType::Slashing => "blade",
+        DamageType::Piercing => "point",
+        DamageType::Bludgeoning => "blow",
+        DamageType::Fire => "fire",
+        DamageType::Cold => "cold",
+        DamageType::Poison => "poison",
+        DamageType::Magic => "magic",
+        DamageType::Hunger => "hunger",
+    }
+}
+
+fn damage_verb(damage_type: DamageType) -> &'static str {
+    match damage_type {
+        DamageType::Slashing => "cuts",
+        DamageType::Piercing => "pierces",
+        DamageType::Bludgeoning => "batters",
+        DamageType::Fire => "scorches",
+        DamageType::Cold => "freezes",
+        DamageType::Poison => "sickens",
+        DamageType::Magic => "blasts",
+        DamageType::Hunger => "gnaws at",
+    }
+}
+
 pub fn delete_the_dead(world: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
 
@@ -60,6 +186,70 @@ pub fn delete_the_dead(world: &mut World) {
     }
 
     for victim in dead {
+        record_unique(world, victim);
+        drop_loot(world, victim);
         world.delete_entity(victim).expect("Unable to delete");
     }
 }
+
+fn record_unique(world: &mut World, victim: Entity) {
+    if world.read_storage::<Boss>().get(victim).is_none() {
+        return;
+    }
+    if let Some(name) = world.read_storage::<Name>().get(victim) {
+        world.write_resource::<SlainUniques>().record(&name.name);
+    }
+}
+
+/// Scatters everything `victim` was carrying onto its tile, then rolls its drop table.
+fn drop_loot(world: &mut World, victim: Entity) {
+    let pos = match world.read_storage::<Position>().get(victim) {
+        None => return,
+        Some(pos) => pos.clone(),
+    };
+    drop_carried(world, victim);
+
+    let table = match world.read_storage::<LootTable>().get(victim) {
+        None => return,
+        Some(loot) => loot.table.clone(),
+    };
+    let map_depth = world.fetch::<Map>().depth;
+    let drop = spawner::loot_table(&table, map_depth).roll(
+        &mut world.write_resource::<RandomNumberGenerator>(),
+        map_depth,
+    );
+    if let Some(drop) = drop {
+        spawner::spawn_entity(world, drop, pos.x, pos.y, map_depth);
+    }
+}
+
+/// Leaves everything `owner` was carrying or wearing on its tile, so nothing is orphaned when
+/// it leaves the level.
+pub fn drop_carried(world: &mut World, owner: Entity) {
+    let pos = match world.read_storage::<Position>().get(owner) {
+        None => return,
+        Some(pos) => pos.clone(),
+    };
+
+    let carried: Vec<Entity> = {
+        let entities = world.entities();
+        let backpack = world.read_storage::<InBackpack>();
+        let equipped = world.read_storage::<Equipped>();
+        (&entities, backpack.maybe(), equipped.maybe())
+            .join()
+            .filter(|(_entity, pack, worn)| {
+                pack.is_some_and(|pack| pack.owner == owner)
+                    || worn.is_some_and(|worn| worn.owner == owner)
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    };
+    for item in carried {
+        world.write_storage::<InBackpack>().remove(item);
+        world.write_storage::<Equipped>().remove(item);
+        world
+            .write_storage::<Position>()
+            .insert(item, pos.clone())
+            .expect("Unable to insert position");
+    }
+}
@@ -0,0 +1,145 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::{
+    components::{Alerted, Asleep, CombatStats, Monster, Position, SuffersDamage},
+    map::Map,
+};
+
+use super::{EffectSpawner, EffectType, EffectsQueue, Targets};
+
+pub fn inflict_damage(world: &mut World, damage: &EffectSpawner, target: Entity) {
+    if let EffectType::Damage {
+        amount,
+        damage_type,
+    } = damage.effect_type
+    {
+        if world.read_storage::<CombatStats>().get(target).is_none() {
+            return;
+        }
+
+        SuffersDamage::new_damage(
+            &mut world.write_storage::<SuffersDamage>(),
+            target,
+            amount,
+            damage_type,
+            damage.creator,
+        );
+
+        // Getting hurt wakes a monster up and sends it looking for whoever did it.
+        if world.read_storage::<Monster>().get(target).is_some() {
+            let origin = {
+                let positions = world.read_storage::<Position>();
+                damage
+                    .creator
+                    .and_then(|creator| positions.get(creator))
+                    .or_else(|| positions.get(target))
+                    .map(|pos| (pos.x, pos.y))
+            };
+            world.write_storage::<Asleep>().remove(target);
+            if let Some((x, y)) = origin {
+                world
+                    .write_storage::<Alerted>()
+                    .insert(target, Alerted::at(x, y))
+                    .expect("Unable to insert alert");
+            }
+        }
+
+        let mut effects = world.write_resource::<EffectsQueue>();
+        effects.add_effect(
+            damage.creator,
+            EffectType::Bloodstain,
+            Targets::Single { target },
+        );
+        effects.add_effect(
+            damage.creator,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('‼'),
+                fg: RGB::named(rltk::ORANGE),
+                bg: RGB::named(rltk::BLACK),
+                lifetime_ms: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
+
+pub fn heal_damage(world: &mut World, heal: &EffectSpawner, target: Entity) {
+    if let EffectType::Healing { amount } = heal.effect_type {
+        let mut combat_stats = world.write_storage::<CombatStats>();
+        if let Some(stats) = combat_stats.get_mut(target) {
+            stats.hp = i32::min(stats.max_hp, stats.hp + amount);
+
+            world.write_resource::<EffectsQueue>().add_effect(
+                heal.creator,
+                EffectType::Particle {
+                    glyph: rltk::to_cp437('♥'),
+                    fg: RGB::named(rltk::GREEN),
+                    bg: RGB::named(rltk::BLACK),
+                    lifetime_ms: 200.0,
+                },
+                Targets::Single { target },
+            );
+        }
+    }
+}
+
+pub fn bloodstain(world: &mut World, tile_idx: usize) {
+    let mut map = world.write_resource::<Map>();
+    map.bloodstains.insert(tile_idx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::DamageType, effects, gamelog::GameLog, noise, particles::ParticlesBuilder,
+    };
+
+    #[test]
+    fn damage_wakes_and_alerts_a_monster() {
+        let mut world = World::new();
+        world.register::<CombatStats>();
+        world.register::<SuffersDamage>();
+        world.register::<Position>();
+        world.register::<Monster>();
+        world.register::<Asleep>();
+        world.register::<Alerted>();
+        world.insert(Map::new(1));
+        world.insert(EffectsQueue::new());
+        world.insert(ParticlesBuilder::new());
+        world.insert(GameLog {
+            entries: Vec::new(),
+        });
+        let monster = world
+            .create_entity()
+            .with(Monster {})
+            .with(Asleep {})
+            .with(Position { x: 5, y: 5 })
+            .with(CombatStats { max_hp: 10, hp: 10 })
+            .build();
+
+        let unaware = |world: &World| {
+            noise::is_unaware(
+                monster,
+                &world.read_storage::<Monster>(),
+                &world.read_storage::<Alerted>(),
+                &world.read_storage::<Asleep>(),
+            )
+        };
+        assert!(unaware(&world));
+
+        world.write_resource::<EffectsQueue>().add_effect(
+            None,
+            EffectType::Damage {
+                amount: 3,
+                damage_type: DamageType::Fire,
+            },
+            Targets::Single { target: monster },
+        );
+        effects::run_effects_queue(&mut world);
+
+        assert!(world.read_storage::<Asleep>().get(monster).is_none());
+        assert!(!unaware(&world));
+    }
+}
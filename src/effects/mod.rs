@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use rltk::{FontCharType, RGB};
+use specs::prelude::*;
+
+use crate::{
+    components::{DamageType, Position, StatusEffectType},
+    map::Map,
+};
+
+mod damage;
+mod movement;
+mod particles;
+mod status;
+
+pub enum EffectType {
+    Damage {
+        amount: i32,
+        damage_type: DamageType,
+    },
+    Healing {
+        amount: i32,
+    },
+    Status {
+        effect: StatusEffectType,
+        turns: i32,
+    },
+    Teleport {
+        x: i32,
+        y: i32,
+    },
+    Particle {
+        glyph: FontCharType,
+        fg: RGB,
+        bg: RGB,
+        lifetime_ms: f32,
+    },
+    Flash {
+        colour: RGB,
+        range: i32,
+        lifetime_ms: f32,
+    },
+    Bloodstain,
+}
+
+#[derive(Clone)]
+pub enum Targets {
+    Single { target: Entity },
+    Tile { tile_idx: usize },
+    Area { tiles: Vec<usize> },
+}
+
+pub struct EffectSpawner {
+    pub creator: Option<Entity>,
+    pub effect_type: EffectType,
+    pub targets: Targets,
+}
+
+pub struct EffectsQueue {
+    requests: VecDeque<EffectSpawner>,
+}
+
+impl EffectsQueue {
+    pub fn new() -> EffectsQueue {
+        EffectsQueue {
+            requests: VecDeque::new(),
+        }
+    }
+
+    pub fn add_effect(
+        &mut self,
+        creator: Option<Entity>,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.requests.push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+        });
+    }
+}
+
+pub fn run_effects_queue(world: &mut World) {
+    loop {
+        let effect = world.write_resource::<EffectsQueue>().requests.pop_front();
+        match effect {
+            None => break,
+            Some(effect) => target_applicator(world, &effect),
+        }
+    }
+}
+
+/// Every entity standing on the targeted tiles, in the order the tiles were given.
+pub fn targeted_entities(map: &Map, targets: &Targets) -> Vec<Entity> {
+    match targets {
+        Targets::Single { target } => vec![*target],
+        Targets::Tile { tile_idx } => map.tile_content[*tile_idx].clone(),
+        Targets::Area { tiles } => tiles
+            .iter()
+            .flat_map(|tile_idx| map.tile_content[*tile_idx].iter().copied())
+            .collect(),
+    }
+}
+
+fn target_applicator(world: &mut World, effect: &EffectSpawner) {
+    match &effect.targets {
+        Targets::Single { target } => affect_entity(world, effect, *target),
+        Targets::Tile { tile_idx } => affect_tile(world, effect, *tile_idx),
+        Targets::Area { tiles } => {
+            for tile_idx in tiles.iter() {
+                affect_tile(world, effect, *tile_idx);
+            }
+        }
+    }
+}
+
+fn affects_entities(effect_type: &EffectType) -> bool {
+    !matches!(
+        effect_type,
+        EffectType::Particle { .. } | EffectType::Flash { .. } | EffectType::Bloodstain
+    )
+}
+
+fn affect_tile(world: &mut World, effect: &EffectSpawner, tile_idx: usize) {
+    if affects_entities(&effect.effect_type) {
+        let content = world.fetch::<Map>().tile_content[tile_idx].clone();
+        for entity in content.iter() {
+            affect_entity(world, effect, *entity);
+        }
+    }
+
+    match &effect.effect_type {
+        EffectType::Bloodstain => damage::bloodstain(world, tile_idx),
+        EffectType::Particle { .. } => particles::particle_to_tile(world, effect, tile_idx),
+        EffectType::Flash { .. } => particles::flash_at_tile(world, effect, tile_idx),
+        _ => {}
+    }
+}
+
+fn affect_entity(world: &mut World, effect: &EffectSpawner, target: Entity) {
+    match &effect.effect_type {
+        EffectType::Damage { .. } => damage::inflict_damage(world, effect, target),
+        EffectType::Healing { .. } => damage::heal_damage(world, effect, target),
+        EffectType::Status { .. } => status::apply_status(world, effect, target),
+        EffectType::Teleport { .. } => movement::apply_teleport(world, effect, target),
+        EffectType::Bloodstain | EffectType::Particle { .. } | EffectType::Flash { .. } => {
+            if let Some(tile_idx) = entity_position(world, target) {
+                affect_tile(world, effect, tile_idx);
+            }
+        }
+    }
+}
+
+pub fn entity_position(world: &World, target: Entity) -> Option<usize> {
+    let positions = world.read_storage::<Position>();
+    let map = world.fetch::<Map>();
+    positions.get(target).map(|pos| map.xy_idx(pos.x, pos.y))
+}
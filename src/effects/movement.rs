@@ -0,0 +1,32 @@
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::components::{EntityMoved, Position, Viewshed};
+
+use super::{EffectSpawner, EffectType};
+
+pub fn apply_teleport(world: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Teleport { x, y } = effect.effect_type {
+        let mut positions = world.write_storage::<Position>();
+        if let Some(pos) = positions.get_mut(target) {
+            pos.x = x;
+            pos.y = y;
+        } else {
+            return;
+        }
+
+        if target == *world.fetch::<Entity>() {
+            let mut player_pos = world.write_resource::<Point>();
+            *player_pos = Point::new(x, y);
+        }
+
+        if let Some(viewshed) = world.write_storage::<Viewshed>().get_mut(target) {
+            viewshed.dirty = true;
+        }
+
+        world
+            .write_storage::<EntityMoved>()
+            .insert(target, EntityMoved {})
+            .expect("Unable to insert marker");
+    }
+}
@@ -0,0 +1,45 @@
+use specs::prelude::*;
+
+use crate::{map::Map, particles::ParticlesBuilder};
+
+use super::{EffectSpawner, EffectType};
+
+pub fn particle_to_tile(world: &mut World, effect: &EffectSpawner, tile_idx: usize) {
+    if let EffectType::Particle {
+        glyph,
+        fg,
+        bg,
+        lifetime_ms,
+    } = effect.effect_type
+    {
+        let map = world.fetch::<Map>();
+        let mut particles_builder = world.write_resource::<ParticlesBuilder>();
+        particles_builder.request(
+            tile_idx as i32 % map.width,
+            tile_idx as i32 / map.width,
+            fg,
+            bg,
+            glyph,
+            lifetime_ms,
+        );
+    }
+}
+
+pub fn flash_at_tile(world: &mut World, effect: &EffectSpawner, tile_idx: usize) {
+    if let EffectType::Flash {
+        colour,
+        range,
+        lifetime_ms,
+    } = effect.effect_type
+    {
+        let map = world.fetch::<Map>();
+        let mut particles_builder = world.write_resource::<ParticlesBuilder>();
+        particles_builder.request_flash(
+            tile_idx as i32 % map.width,
+            tile_idx as i32 / map.width,
+            colour,
+            range,
+            lifetime_ms,
+        );
+    }
+}
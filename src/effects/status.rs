@@ -0,0 +1,32 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::components::{CombatStats, StatusEffects};
+
+use super::{EffectSpawner, EffectType, EffectsQueue, Targets};
+
+pub fn apply_status(world: &mut World, status: &EffectSpawner, target: Entity) {
+    if let EffectType::Status { effect, turns } = status.effect_type {
+        if world.read_storage::<CombatStats>().get(target).is_none() {
+            return;
+        }
+
+        StatusEffects::new_status(
+            &mut world.write_storage::<StatusEffects>(),
+            target,
+            effect,
+            turns,
+        );
+
+        world.write_resource::<EffectsQueue>().add_effect(
+            status.creator,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('?'),
+                fg: RGB::named(rltk::MAGENTA),
+                bg: RGB::named(rltk::BLACK),
+                lifetime_ms: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{Attributes, Equipped, InBackpack, Item, Overburdened, Stackable},
+    gamelog::GameLog,
+};
+
+/// Subtracted from the melee to-hit roll of an overburdened entity.
+pub const OVERBURDENED_PENALTY: i32 = 2;
+
+pub struct EncumbranceSystem {}
+
+impl<'a> System<'a> for EncumbranceSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Stackable>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Overburdened>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            items,
+            stackables,
+            backpack,
+            equipped,
+            attributes,
+            mut overburdened,
+            mut log,
+        ) = data;
+
+        let mut carried: HashMap<Entity, f32> = HashMap::new();
+        for (item, stack, carried_by) in (&items, stackables.maybe(), &backpack).join() {
+            *carried.entry(carried_by.owner).or_insert(0.0) += stack_weight(item, stack);
+        }
+        for (item, worn_by) in (&items, &equipped).join() {
+            *carried.entry(worn_by.owner).or_insert(0.0) += item.weight;
+        }
+
+        for (entity, entity_attributes) in (&entities, &attributes).join() {
+            let weight = carried.get(&entity).copied().unwrap_or(0.0);
+            let capacity = attributes::carry_capacity(entity_attributes) as f32;
+            let was_overburdened = overburdened.get(entity).is_some();
+
+            if weight > capacity && !was_overburdened {
+                overburdened
+                    .insert(entity, Overburdened {})
+                    .expect("Unable to insert overburdened");
+                if entity == *player_entity {
+                    log.entries
+                        .push("You are overburdened, and your attacks become clumsy.".to_string());
+                }
+            } else if weight <= capacity && was_overburdened {
+                overburdened.remove(entity);
+                if entity == *player_entity {
+                    log.entries
+                        .push("You are no longer overburdened.".to_string());
+                }
+            }
+        }
+    }
+}
+
+pub fn carried_weight(world: &World, owner: Entity) -> f32 {
+    let items = world.read_storage::<Item>();
+    let stackables = world.read_storage::<Stackable>();
+    let backpack = world.read_storage::<InBackpack>();
+    let equipped = world.read_storage::<Equipped>();
+
+    let packed: f32 = (&items, stackables.maybe(), &backpack)
+        .join()
+        .filter(|(_item, _stack, carried_by)| carried_by.owner == owner)
+        .map(|(item, stack, _carried_by)| stack_weight(item, stack))
+        .sum();
+    let worn: f32 = (&items, &equipped)
+        .join()
+        .filter(|(_item, worn_by)| worn_by.owner == owner)
+        .map(|(item, _worn_by)| item.weight)
+        .sum();
+    packed + worn
+}
+
+pub fn stack_weight(item: &Item, stack: Option<&Stackable>) -> f32 {
+    item.weight * stack.map_or(1, |stack| stack.quantity) as f32
+}
@@ -1,18 +1,28 @@
-use std::usize;
-
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
 
 use crate::{
+    attributes,
+    character_creation::{CharacterBuilder, ATTRIBUTE_NAMES, BACKGROUNDS, MAX_ATTRIBUTE},
     components::{
-        CombatStats, Equipped, Hidden, HungerClock, HungerState, InBackpack, Name, Player,
-        Position, Viewshed,
+        Alerted, AreaOfEffect, Asleep, Attributes, Charges, CombatStats, Cursed, DefenseBonus,
+        EquipmentSlot, Equippable, Equipped, Experience, Hidden, HungerClock, HungerState,
+        InBackpack, InflictsDamage, InflictsStatus, Item, ItemCategory, KnownSpells, MagicItem,
+        MagicItemClass, MagicMapper, Mana, MeleeWeapon, Monster, Name, NaturalAttack, Overburdened,
+        Player, Position, ProvidesFood, ProvidesHealing, Ranged, Resistances, Skills,
+        SpellTemplate, Stackable, StatusEffectType, StatusEffects, TwoHanded, Vendor, Viewshed,
+        Wallet,
     },
+    encumbrance,
     gamelog::GameLog,
+    identification::ItemNameTable,
+    leveling::LEVEL_UP_CHOICES,
+    lighting,
     map::Map,
     saveload::is_game_saved,
+    spells,
     state::RunState,
-    State,
+    vendors, State,
 };
 
 #[derive(PartialEq, Copy, Clone)]
@@ -35,210 +45,968 @@ pub enum MainMenuResult {
     Selected { selected: MainMenuSelection },
 }
 
+#[derive(PartialEq, Copy, Clone)]
+pub enum CreationStep {
+    Name,
+    Background,
+    Attributes,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CharacterCreationResult {
+    NoSelection {
+        step: CreationStep,
+        selection: usize,
+    },
+    Cancel,
+    Completed,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpResult {
+    NoSelection { selection: usize },
+    Selected { selection: usize },
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult {
     NoSelection,
     QuitToMenu,
 }
 
-pub fn show_inventory(game_state: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = game_state.world.fetch::<Entity>();
-    let names = game_state.world.read_storage::<Name>();
-    let backpack = game_state.world.read_storage::<InBackpack>();
-    let entities = game_state.world.entities();
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemFilter {
+    All,
+    Weapons,
+    Armor,
+    Consumables,
+    Scrolls,
+}
+
+const ITEM_FILTERS: [(ItemFilter, &str); 5] = [
+    (ItemFilter::All, "All"),
+    (ItemFilter::Weapons, "Weapons"),
+    (ItemFilter::Armor, "Armor"),
+    (ItemFilter::Consumables, "Consumables"),
+    (ItemFilter::Scrolls, "Scrolls"),
+];
+
+impl ItemFilter {
+    fn matches(self, category: ItemCategory) -> bool {
+        match self {
+            ItemFilter::All => true,
+            ItemFilter::Weapons => category == ItemCategory::Weapon,
+            ItemFilter::Armor => matches!(category, ItemCategory::Armor | ItemCategory::Jewelry),
+            ItemFilter::Consumables => {
+                matches!(
+                    category,
+                    ItemCategory::Potion | ItemCategory::Wand | ItemCategory::Food
+                )
+            }
+            ItemFilter::Scrolls => category == ItemCategory::Scroll,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemSort {
+    Name,
+    Weight,
+    Type,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub struct ItemMenuState {
+    pub selection: usize,
+    pub filter: ItemFilter,
+    pub sort: ItemSort,
+    pub inspecting: bool,
+}
+
+impl Default for ItemMenuState {
+    fn default() -> Self {
+        ItemMenuState {
+            selection: 0,
+            filter: ItemFilter::All,
+            sort: ItemSort::Name,
+            inspecting: false,
+        }
+    }
+}
+
+const MENU_ROWS: usize = 16;
+
+pub fn show_inventory(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let capacity = world
+        .read_storage::<Attributes>()
+        .get(player_entity)
+        .map_or(0, attributes::carry_capacity);
+    let title = format!(
+        "Inventory ({:.1}/{} lbs)",
+        encumbrance::carried_weight(world, player_entity),
+        capacity
+    );
+    let items = (&world.entities(), &world.read_storage::<InBackpack>())
+        .join()
+        .filter(|(_entity, pack)| pack.owner == player_entity)
+        .map(|(entity, _pack)| entity)
+        .collect();
+    item_menu(world, ctx, &title, items, menu, None)
+}
+
+pub fn show_drop_item(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let items = (&world.entities(), &world.read_storage::<InBackpack>())
+        .join()
+        .filter(|(_entity, pack)| pack.owner == player_entity)
+        .map(|(entity, _pack)| entity)
+        .collect();
+    item_menu(world, ctx, "Drop Which Item?", items, menu, None)
+}
+
+pub fn show_vendor(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    vendor: Entity,
+    mode: VendorMode,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, VendorMode, ItemMenuState) {
+    match ctx.key {
+        Some(VirtualKeyCode::Key1) if mode != VendorMode::Buy => {
+            return (
+                ItemMenuResult::NoResponse,
+                None,
+                VendorMode::Buy,
+                ItemMenuState::default(),
+            )
+        }
+        Some(VirtualKeyCode::Key2) if mode != VendorMode::Sell => {
+            return (
+                ItemMenuResult::NoResponse,
+                None,
+                VendorMode::Sell,
+                ItemMenuState::default(),
+            )
+        }
+        _ => {}
+    }
+
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let vendor_name = world
+        .read_storage::<Name>()
+        .get(vendor)
+        .map_or(String::new(), |name| name.name.clone());
+    let gold = world
+        .read_storage::<Wallet>()
+        .get(player_entity)
+        .map_or(0, |wallet| wallet.gold);
+    let categories = world
+        .read_storage::<Vendor>()
+        .get(vendor)
+        .map_or(Vec::new(), |vendor| vendor.categories.clone());
 
-    let inventory = (&backpack, &names)
+    let (owner, title, price): (Entity, String, fn(&Item) -> i32) = match mode {
+        VendorMode::Buy => (
+            vendor,
+            format!("Buy from the {} ({} gold)", vendor_name, gold),
+            vendors::buy_price,
+        ),
+        VendorMode::Sell => (
+            player_entity,
+            format!("Sell to the {} ({} gold)", vendor_name, gold),
+            vendors::sell_price,
+        ),
+    };
+    let backpack = world.read_storage::<InBackpack>();
+    let item_info = world.read_storage::<Item>();
+    let items = (&world.entities(), &backpack, &item_info)
         .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+        .filter(|(_entity, pack, item)| pack.owner == owner && categories.contains(&item.category))
+        .map(|(entity, ..)| entity)
+        .collect();
+
+    ctx.print_color(
+        3,
+        5,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "1 Buy  2 Sell",
+    );
+    let (result, item, menu) = item_menu(world, ctx, &title, items, menu, Some(price));
+    (result, item, mode, menu)
+}
+
+pub fn show_throw_item(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let backpack = world.read_storage::<InBackpack>();
+    let items = (&world.entities(), &backpack)
+        .join()
+        .filter(|(_entity, pack)| pack.owner == player_entity)
+        .map(|(entity, _pack)| entity)
+        .collect();
+    item_menu(world, ctx, "Throw Which Item?", items, menu, None)
+}
+
+pub fn show_identify(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let names = world.read_storage::<Name>();
+    let item_names = world.fetch::<ItemNameTable>();
+    let backpack = world.read_storage::<InBackpack>();
+    let equipped = world.read_storage::<Equipped>();
+    let items = (&world.entities(), &names)
+        .join()
+        .filter(|(entity, name)| {
+            let carried = backpack
+                .get(*entity)
+                .is_some_and(|pack| pack.owner == player_entity)
+                || equipped
+                    .get(*entity)
+                    .is_some_and(|worn| worn.owner == player_entity);
+            carried && !item_names.is_identified(&name.name)
+        })
+        .map(|(entity, _name)| entity)
+        .collect();
+    item_menu(world, ctx, "Identify Which Item?", items, menu, None)
+}
+
+pub fn show_recharge(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let backpack = world.read_storage::<InBackpack>();
+    let charges = world.read_storage::<Charges>();
+    let items = (&world.entities(), &backpack, &charges)
+        .join()
+        .filter(|(_entity, pack, _charges)| pack.owner == player_entity)
+        .map(|(entity, _pack, _charges)| entity)
+        .collect();
+    item_menu(world, ctx, "Recharge Which Item?", items, menu, None)
+}
+
+pub fn remove_item_menu(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    menu: ItemMenuState,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let world = &game_state.world;
+    let player_entity = *world.fetch::<Entity>();
+    let items = (&world.entities(), &world.read_storage::<Equipped>())
+        .join()
+        .filter(|(_entity, worn)| worn.owner == player_entity)
+        .map(|(entity, _worn)| entity)
+        .collect();
+    item_menu(world, ctx, "Remove Which Item?", items, menu, None)
+}
+
+/// Shared list used by every item prompt: filter tabs, sorting, a scrolling window with
+/// letter shortcuts for the visible rows, and an optional inspect panel.
+fn item_menu(
+    world: &World,
+    ctx: &mut Rltk,
+    title: &str,
+    items: Vec<Entity>,
+    mut menu: ItemMenuState,
+    price: Option<fn(&Item) -> i32>,
+) -> (ItemMenuResult, Option<Entity>, ItemMenuState) {
+    let names = world.read_storage::<Name>();
+    let item_info = world.read_storage::<Item>();
+    let stackables = world.read_storage::<Stackable>();
+    let charges = world.read_storage::<Charges>();
+    let item_names = world.fetch::<ItemNameTable>();
+    let magic_items = world.read_storage::<MagicItem>();
+
+    let mut rows: Vec<(Entity, String, ItemCategory, f32, RGB, String)> = items
+        .iter()
+        .filter_map(|entity| {
+            let item = item_info.get(*entity)?;
+            let name = names.get(*entity)?;
+            if !menu.filter.matches(item.category) {
+                return None;
+            }
+            let stack = stackables.get(*entity);
+            Some((
+                *entity,
+                item_label(
+                    &item_names.display_name(&name.name),
+                    stack,
+                    charges.get(*entity),
+                ),
+                item.category,
+                encumbrance::stack_weight(item, stack),
+                rarity_colour(magic_items.get(*entity)),
+                match price {
+                    Some(price) => format!("{:>5} gp", price(item)),
+                    None => format!("{:>5.1} lb", encumbrance::stack_weight(item, stack)),
+                },
+            ))
+        })
+        .collect();
+    match menu.sort {
+        ItemSort::Name => rows.sort_by(|a, b| a.1.cmp(&b.1)),
+        ItemSort::Weight => rows.sort_by(|a, b| b.3.total_cmp(&a.3)),
+        ItemSort::Type => rows.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.1.cmp(&b.1))),
+    }
+
+    menu.selection = usize::min(menu.selection, rows.len().saturating_sub(1));
+    let offset = (menu.selection + 1).saturating_sub(MENU_ROWS);
 
-    let mut y = (25 - (count / 2)) as i32;
     ctx.draw_box(
-        15,
-        y - 2,
-        31,
-        (count + 3) as i32,
+        1,
+        7,
+        46,
+        MENU_ROWS as i32 + 8,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
     ctx.print_color(
-        18,
-        y - 2,
+        3,
+        7,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Inventory",
+        title,
     );
+
+    let mut x = 3;
+    for (filter, label) in ITEM_FILTERS.iter() {
+        let fg = if *filter == menu.filter {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::GRAY)
+        };
+        ctx.print_color(x, 8, fg, RGB::named(rltk::BLACK), label);
+        x += label.len() as i32 + 2;
+    }
+    let sort = match menu.sort {
+        ItemSort::Name => "name",
+        ItemSort::Weight => "weight",
+        ItemSort::Type => "type",
+    };
     ctx.print_color(
-        18,
-        y + count as i32 + 1,
-        RGB::named(rltk::YELLOW),
+        3,
+        9,
+        RGB::named(rltk::GRAY),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        format!("Sorted by {}", sort),
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
+    if rows.is_empty() {
+        ctx.print_color(
+            3,
+            11,
+            RGB::named(rltk::GRAY),
+            RGB::named(rltk::BLACK),
+            "Nothing here.",
+        );
+    }
+    for (j, (_entity, label, _category, _weight, rarity, detail)) in
+        rows.iter().enumerate().skip(offset).take(MENU_ROWS)
     {
+        let row = j - offset;
+        let y = 11 + row as i32;
+        let (fg, bg) = if j == menu.selection {
+            (RGB::named(rltk::WHITE), RGB::named(rltk::DARK_BLUE))
+        } else {
+            (RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
+        };
+        ctx.set(3, y, fg, bg, rltk::to_cp437('('));
         ctx.set(
-            17,
+            4,
             y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
+            RGB::named(rltk::YELLOW),
+            bg,
+            97 + row as rltk::FontCharType,
         );
+        ctx.set(5, y, fg, bg, rltk::to_cp437(')'));
+        ctx.print_color(7, y, *rarity, bg, label);
+        ctx.print_color(36, y, RGB::named(rltk::GRAY), bg, detail);
+    }
+    if offset > 0 {
         ctx.set(
-            18,
-            y,
+            46,
+            11,
             RGB::named(rltk::YELLOW),
             RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
+            rltk::to_cp437('↑'),
         );
+    }
+    if offset + MENU_ROWS < rows.len() {
         ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
+            46,
+            10 + MENU_ROWS as i32,
+            RGB::named(rltk::YELLOW),
             RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
+            rltk::to_cp437('↓'),
         );
+    }
 
-        ctx.print(31, y, &name.name.to_string());
-
-        equippable.push(entity);
+    ctx.print_color(
+        3,
+        13 + MENU_ROWS as i32,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "Left/Right filter, Tab sort, Space inspect",
+    );
+    ctx.print_color(
+        3,
+        14 + MENU_ROWS as i32,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Enter or letter to select, ESCAPE to cancel",
+    );
 
-        y += 1;
-        j += 1;
+    let selected = rows.get(menu.selection).map(|row| row.0);
+    if menu.inspecting {
+        if let Some(item) = selected {
+            draw_inspect_panel(world, ctx, item);
+        }
     }
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
+        None => {}
         Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            VirtualKeyCode::Escape => {
+                if !menu.inspecting {
+                    return (ItemMenuResult::Cancel, None, menu);
+                }
+                menu.inspecting = false;
+            }
+            VirtualKeyCode::Up => menu.selection = menu.selection.saturating_sub(1),
+            VirtualKeyCode::Down => menu.selection += 1,
+            VirtualKeyCode::PageUp => menu.selection = menu.selection.saturating_sub(MENU_ROWS),
+            VirtualKeyCode::PageDown => menu.selection += MENU_ROWS,
+            VirtualKeyCode::Left | VirtualKeyCode::Right => {
+                let current = ITEM_FILTERS
+                    .iter()
+                    .position(|(filter, _label)| *filter == menu.filter)
+                    .unwrap_or(0);
+                let next = if key == VirtualKeyCode::Left {
+                    (current + ITEM_FILTERS.len() - 1) % ITEM_FILTERS.len()
+                } else {
+                    (current + 1) % ITEM_FILTERS.len()
+                };
+                menu.filter = ITEM_FILTERS[next].0;
+                menu.selection = 0;
+            }
+            VirtualKeyCode::Tab => {
+                menu.sort = match menu.sort {
+                    ItemSort::Name => ItemSort::Weight,
+                    ItemSort::Weight => ItemSort::Type,
+                    ItemSort::Type => ItemSort::Name,
+                };
+            }
+            VirtualKeyCode::Space => menu.inspecting = !menu.inspecting,
+            VirtualKeyCode::Return => {
+                if selected.is_some() {
+                    return (ItemMenuResult::Selected, selected, menu);
+                }
+            }
             _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+                let shortcut = rltk::letter_to_option(key);
+                if shortcut > -1 {
+                    if let Some(row) = rows.get(offset + shortcut as usize) {
+                        if (shortcut as usize) < MENU_ROWS {
+                            return (ItemMenuResult::Selected, Some(row.0), menu);
+                        }
+                    }
                 }
-                (ItemMenuResult::NoResponse, None)
             }
         },
     }
-}
 
-pub fn show_drop_item(game_state: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = game_state.world.fetch::<Entity>();
-    let names = game_state.world.read_storage::<Name>();
-    let backpack = game_state.world.read_storage::<InBackpack>();
-    let entities = game_state.world.entities();
+    (ItemMenuResult::NoResponse, None, menu)
+}
 
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+fn draw_inspect_panel(world: &World, ctx: &mut Rltk, item: Entity) {
+    let lines = describe_item(world, item);
+    let name = world
+        .read_storage::<Name>()
+        .get(item)
+        .map_or(String::new(), |name| {
+            world.fetch::<ItemNameTable>().display_name(&name.name)
+        });
 
-    let mut y = (25 - (count / 2)) as i32;
     ctx.draw_box(
-        15,
-        y - 2,
-        31,
-        (count + 3) as i32,
+        48,
+        7,
+        30,
+        MENU_ROWS as i32 + 8,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
     ctx.print_color(
-        18,
-        y - 2,
+        50,
+        7,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Drop Which Item?",
+        name,
     );
-    ctx.print_color(
-        18,
-        y + count as i32 + 1,
+    for (i, line) in lines.iter().enumerate() {
+        ctx.print(50, 9 + i as i32, line);
+    }
+}
+
+fn rarity_colour(magic: Option<&MagicItem>) -> RGB {
+    match magic.map(|magic| magic.class) {
+        Some(MagicItemClass::Rare) => RGB::named(rltk::GOLD),
+        Some(MagicItemClass::Magic) => RGB::named(rltk::CYAN),
+        _ => RGB::named(rltk::WHITE),
+    }
+}
+
+fn describe_item(world: &World, item: Entity) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(info) = world.read_storage::<Item>().get(item) {
+        lines.push(format!("{:?}", info.category));
+        lines.push(format!("Weight: {:.1} lb", info.weight));
+        lines.push(format!("Value: {} gold", info.base_value));
+    }
+    let identified = world
+        .read_storage::<Name>()
+        .get(item)
+        .is_none_or(|name| world.fetch::<ItemNameTable>().is_identified(&name.name));
+    if !identified {
+        lines.push("Unidentified".to_string());
+        return lines;
+    }
+    if let Some(magic) = world.read_storage::<MagicItem>().get(item) {
+        lines.push(format!("{:?} item", magic.class));
+    }
+    let worn = world.read_storage::<Equipped>().get(item).is_some();
+    if worn && world.read_storage::<Cursed>().get(item).is_some() {
+        lines.push("Cursed".to_string());
+    }
+    if let Some(charges) = world.read_storage::<Charges>().get(item) {
+        lines.push(format!("Charges: {} / {}", charges.current, charges.max));
+    }
+    if let Some(stack) = world.read_storage::<Stackable>().get(item) {
+        if stack.quantity > 1 {
+            lines.push(format!("Quantity: {}", stack.quantity));
+        }
+    }
+    if let Some(equippable) = world.read_storage::<Equippable>().get(item) {
+        if let Some((_slot, label)) = EQUIPMENT_SLOTS
+            .iter()
+            .find(|(slot, _label)| *slot == equippable.slot)
+        {
+            lines.push(format!("Slot: {}", label));
+        }
+    }
+    if world.read_storage::<TwoHanded>().get(item).is_some() {
+        lines.push("Two-handed".to_string());
+    }
+    if let Some(weapon) = world.read_storage::<MeleeWeapon>().get(item) {
+        lines.push(format!(
+            "Damage: {} {:?}",
+            dice_label(
+                weapon.damage.n_dice,
+                weapon.damage.die_type,
+                weapon.damage.bonus
+            ),
+            weapon.damage_type
+        ));
+        lines.push(format!("To hit: {:+}", weapon.hit_bonus));
+    }
+    if let Some(defense) = world.read_storage::<DefenseBonus>().get(item) {
+        lines.push(format!("Armor: {:+}", defense.defense));
+    }
+    if let Some(resistances) = world.read_storage::<Resistances>().get(item) {
+        for (damage_type, level) in resistances.resistances.iter() {
+            lines.push(format!("{:?} vs {:?}", level, damage_type));
+        }
+    }
+    if let Some(healing) = world.read_storage::<ProvidesHealing>().get(item) {
+        lines.push(format!("Heals {} hp", healing.heal_amount));
+    }
+    if let Some(damage) = world.read_storage::<InflictsDamage>().get(item) {
+        lines.push(format!(
+            "Deals {} {:?} damage",
+            damage.damage, damage.damage_type
+        ));
+    }
+    if let Some(status) = world.read_storage::<InflictsStatus>().get(item) {
+        lines.push(format!(
+            "Inflicts {:?} ({} turns)",
+            status.effect, status.turns
+        ));
+    }
+    if let Some(ranged) = world.read_storage::<Ranged>().get(item) {
+        lines.push(format!("Range: {}", ranged.range));
+    }
+    if let Some(area) = world.read_storage::<AreaOfEffect>().get(item) {
+        lines.push(format!("Area radius: {}", area.radius));
+    }
+    if world.read_storage::<ProvidesFood>().get(item).is_some() {
+        lines.push("Satisfies hunger".to_string());
+    }
+    if world.read_storage::<MagicMapper>().get(item).is_some() {
+        lines.push("Reveals the level map".to_string());
+    }
+
+    lines
+}
+
+fn dice_label(n_dice: i32, die_type: i32, bonus: i32) -> String {
+    if bonus == 0 {
+        format!("{}d{}", n_dice, die_type)
+    } else {
+        format!("{}d{}{:+}", n_dice, die_type, bonus)
+    }
+}
+
+fn item_label(name: &str, stack: Option<&Stackable>, charges: Option<&Charges>) -> String {
+    match (stack, charges) {
+        (Some(stack), _) if stack.quantity > 1 => format!("{} (x{})", name, stack.quantity),
+        (_, Some(charges)) => format!("{} [{}/{}]", name, charges.current, charges.max),
+        _ => name.to_string(),
+    }
+}
+
+pub fn character_creation(
+    game_state: &mut State,
+    ctx: &mut Rltk,
+    step: CreationStep,
+    selection: usize,
+) -> CharacterCreationResult {
+    let mut builder = game_state.world.fetch_mut::<CharacterBuilder>();
+
+    ctx.draw_box_double(
+        15,
+        12,
+        49,
+        20,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        13,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        "Create Your Character",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
-        ctx.set(
-            17,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
-        );
-        ctx.set(
-            18,
-            y,
-            RGB::named(rltk::YELLOW),
-            RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
-        );
-        ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
-        );
+    match step {
+        CreationStep::Name => {
+            ctx.print_color_centered(
+                16,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                "What is your name?",
+            );
+            ctx.print_color_centered(
+                18,
+                RGB::named(rltk::MAGENTA),
+                RGB::named(rltk::BLACK),
+                format!("{}_", builder.name),
+            );
+            ctx.print_color_centered(
+                30,
+                RGB::named(rltk::GRAY),
+                RGB::named(rltk::BLACK),
+                "Type a name, Enter to continue, ESCAPE to cancel",
+            );
 
-        ctx.print(31, y, &name.name.to_string());
+            match ctx.key {
+                None => {}
+                Some(VirtualKeyCode::Escape) => return CharacterCreationResult::Cancel,
+                Some(VirtualKeyCode::Back) => {
+                    builder.name.pop();
+                }
+                Some(VirtualKeyCode::Return) if !builder.name.is_empty() => {
+                    return CharacterCreationResult::NoSelection {
+                        step: CreationStep::Background,
+                        selection: 0,
+                    };
+                }
+                Some(key) => {
+                    let letter = rltk::letter_to_option(key);
+                    if letter > -1 {
+                        builder.push_letter((b'a' + letter as u8) as char);
+                    }
+                }
+            }
+        }
+        CreationStep::Background => {
+            ctx.print_color_centered(
+                15,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                format!("Choose a background for {}", builder.name),
+            );
+            for (i, background) in BACKGROUNDS.iter().enumerate() {
+                let y = 17 + i as i32 * 3;
+                let fg = if i == selection {
+                    RGB::named(rltk::MAGENTA)
+                } else {
+                    RGB::named(rltk::WHITE)
+                };
+                ctx.print_color(18, y, fg, RGB::named(rltk::BLACK), background.name());
+                ctx.print_color(
+                    20,
+                    y + 1,
+                    RGB::named(rltk::GRAY),
+                    RGB::named(rltk::BLACK),
+                    background.description(),
+                );
+            }
+            ctx.print_color_centered(
+                30,
+                RGB::named(rltk::GRAY),
+                RGB::named(rltk::BLACK),
+                "Up/Down to choose, Enter to continue, ESCAPE to go back",
+            );
 
-        equippable.push(entity);
+            match ctx.key {
+                None => {}
+                Some(VirtualKeyCode::Escape) => {
+                    return CharacterCreationResult::NoSelection {
+                        step: CreationStep::Name,
+                        selection: 0,
+                    }
+                }
+                Some(VirtualKeyCode::Up) => {
+                    return CharacterCreationResult::NoSelection {
+                        step,
+                        selection: (selection + BACKGROUNDS.len() - 1) % BACKGROUNDS.len(),
+                    }
+                }
+                Some(VirtualKeyCode::Down) => {
+                    return CharacterCreationResult::NoSelection {
+                        step,
+                        selection: (selection + 1) % BACKGROUNDS.len(),
+                    }
+                }
+                Some(VirtualKeyCode::Return) => {
+                    builder.background = BACKGROUNDS[selection];
+                    return CharacterCreationResult::NoSelection {
+                        step: CreationStep::Attributes,
+                        selection: 0,
+                    };
+                }
+                _ => {}
+            }
+        }
+        CreationStep::Attributes => {
+            ctx.print_color_centered(
+                15,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                format!(
+                    "{} the {}: {} points to spend",
+                    builder.name,
+                    builder.background.name(),
+                    builder.points_remaining()
+                ),
+            );
+            for (i, attribute) in ATTRIBUTE_NAMES.iter().enumerate() {
+                let y = 17 + i as i32;
+                let fg = if i == selection {
+                    RGB::named(rltk::MAGENTA)
+                } else {
+                    RGB::named(rltk::WHITE)
+                };
+                ctx.print_color(20, y, fg, RGB::named(rltk::BLACK), attribute);
+                ctx.print_color(
+                    36,
+                    y,
+                    fg,
+                    RGB::named(rltk::BLACK),
+                    format!("{:>2} / {}", builder.attribute(i), MAX_ATTRIBUTE),
+                );
+            }
 
-        y += 1;
-        j += 1;
-    }
+            let skills = builder.background.skills();
+            ctx.print_color(
+                20,
+                22,
+                RGB::named(rltk::YELLOW),
+                RGB::named(rltk::BLACK),
+                format!(
+                    "HP: {}  Mana: {}",
+                    attributes::player_max_hp(&builder.attributes, 1),
+                    attributes::max_mana(&builder.attributes, &skills, 1)
+                ),
+            );
+            ctx.print_color(
+                20,
+                23,
+                RGB::named(rltk::YELLOW),
+                RGB::named(rltk::BLACK),
+                format!(
+                    "Carry capacity: {} lbs",
+                    attributes::carry_capacity(&builder.attributes)
+                ),
+            );
+            ctx.print_color(
+                20,
+                24,
+                RGB::named(rltk::YELLOW),
+                RGB::named(rltk::BLACK),
+                format!(
+                    "To hit: {:+}  Armor class: {}",
+                    attributes::melee_to_hit(&builder.attributes, &skills),
+                    attributes::armor_class(&builder.attributes, &skills)
+                ),
+            );
+            ctx.print_color_centered(
+                30,
+                RGB::named(rltk::GRAY),
+                RGB::named(rltk::BLACK),
+                "Arrows to assign, Enter to begin, ESCAPE to go back",
+            );
 
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+            match ctx.key {
+                None => {}
+                Some(VirtualKeyCode::Escape) => {
+                    return CharacterCreationResult::NoSelection {
+                        step: CreationStep::Background,
+                        selection: 0,
+                    }
+                }
+                Some(VirtualKeyCode::Up) => {
+                    return CharacterCreationResult::NoSelection {
+                        step,
+                        selection: (selection + ATTRIBUTE_NAMES.len() - 1) % ATTRIBUTE_NAMES.len(),
+                    }
+                }
+                Some(VirtualKeyCode::Down) => {
+                    return CharacterCreationResult::NoSelection {
+                        step,
+                        selection: (selection + 1) % ATTRIBUTE_NAMES.len(),
+                    }
                 }
-                (ItemMenuResult::NoResponse, None)
+                Some(VirtualKeyCode::Left) => builder.adjust_attribute(selection, -1),
+                Some(VirtualKeyCode::Right) => builder.adjust_attribute(selection, 1),
+                Some(VirtualKeyCode::Return) => return CharacterCreationResult::Completed,
+                _ => {}
             }
+        }
+    }
+
+    CharacterCreationResult::NoSelection { step, selection }
+}
+
+pub fn level_up_menu(game_state: &mut State, ctx: &mut Rltk, selection: usize) -> LevelUpResult {
+    let player_entity = game_state.world.fetch::<Entity>();
+    let experience = game_state.world.read_storage::<Experience>();
+    let level = experience.get(*player_entity).map_or(1, |xp| xp.level) + 1;
+
+    let count = LEVEL_UP_CHOICES.len() as i32;
+    let y = 25 - count / 2;
+    ctx.draw_box(
+        20,
+        y - 3,
+        39,
+        count + 4,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        23,
+        y - 3,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!("Welcome to level {}!", level),
+    );
+    ctx.print_color(
+        23,
+        y - 1,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        "Choose something to improve:",
+    );
+    for (i, choice) in LEVEL_UP_CHOICES.iter().enumerate() {
+        let fg = if i == selection {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+        ctx.print_color(25, y + i as i32, fg, RGB::named(rltk::BLACK), choice);
+    }
+    ctx.print_color(
+        23,
+        y + count + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Up/Down and Enter to choose",
+    );
+
+    match ctx.key {
+        Some(VirtualKeyCode::Up) => LevelUpResult::NoSelection {
+            selection: (selection + LEVEL_UP_CHOICES.len() - 1) % LEVEL_UP_CHOICES.len(),
+        },
+        Some(VirtualKeyCode::Down) => LevelUpResult::NoSelection {
+            selection: (selection + 1) % LEVEL_UP_CHOICES.len(),
         },
+        Some(VirtualKeyCode::Return) => LevelUpResult::Selected { selection },
+        _ => LevelUpResult::NoSelection { selection },
     }
 }
 
-pub fn remove_item_menu(
-    game_state: &mut State,
-    ctx: &mut Rltk,
-) -> (ItemMenuResult, Option<Entity>) {
+const EQUIPMENT_SLOTS: [(EquipmentSlot, &str); 11] = [
+    (EquipmentSlot::Head, "Head"),
+    (EquipmentSlot::Amulet, "Amulet"),
+    (EquipmentSlot::Torso, "Torso"),
+    (EquipmentSlot::Hands, "Hands"),
+    (EquipmentSlot::LeftRing, "Left ring"),
+    (EquipmentSlot::RightRing, "Right ring"),
+    (EquipmentSlot::Legs, "Legs"),
+    (EquipmentSlot::Feet, "Feet"),
+    (EquipmentSlot::Melee, "Melee"),
+    (EquipmentSlot::Shield, "Shield"),
+    (EquipmentSlot::Ranged, "Ranged"),
+];
+
+pub fn show_equipment(game_state: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
     let player_entity = game_state.world.fetch::<Entity>();
     let names = game_state.world.read_storage::<Name>();
-    let backpack = game_state.world.read_storage::<Equipped>();
+    let equipped = game_state.world.read_storage::<Equipped>();
+    let defense_bonuses = game_state.world.read_storage::<DefenseBonus>();
+    let melee_weapons = game_state.world.read_storage::<MeleeWeapon>();
+    let natural_attacks = game_state.world.read_storage::<NaturalAttack>();
+    let two_handed = game_state.world.read_storage::<TwoHanded>();
+    let attributes = game_state.world.read_storage::<Attributes>();
+    let skills = game_state.world.read_storage::<Skills>();
     let entities = game_state.world.entities();
 
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
-
-    let mut y = (25 - (count / 2)) as i32;
+    let count = EQUIPMENT_SLOTS.len() as i32;
+    let y = 21 - count / 2;
     ctx.draw_box(
         15,
         y - 2,
-        31,
-        (count + 3) as i32,
+        49,
+        count + 8,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
@@ -247,66 +1015,118 @@ pub fn remove_item_menu(
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Remove Which Item?",
+        "Equipment",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        y + count + 6,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        "ESCAPE to close",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
-        ctx.set(
+    let mut gear_defense = 0;
+    let mut two_handed_weapon = false;
+    let mut weapon = natural_attacks
+        .get(*player_entity)
+        .map(|attack| (attack.damage, attack.hit_bonus, attack.damage_type));
+    for (i, (slot, label)) in EQUIPMENT_SLOTS.iter().enumerate() {
+        let y = y + i as i32;
+        ctx.print_color(
             17,
             y,
-            RGB::named(rltk::WHITE),
+            RGB::named(rltk::GRAY),
             RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
+            format!("{}:", label),
         );
-        ctx.set(
-            18,
-            y,
-            RGB::named(rltk::YELLOW),
-            RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
+
+        let mut description = "-".to_string();
+        for (item, worn, name) in (&entities, &equipped, &names).join() {
+            if worn.owner != *player_entity || worn.slot != *slot {
+                continue;
+            }
+            description = name.name.to_string();
+            if let Some(bonus) = defense_bonuses.get(item) {
+                gear_defense += bonus.defense;
+                description = format!("{} (+{} AC)", description, bonus.defense);
+            }
+            if let Some(melee) = melee_weapons.get(item) {
+                weapon = Some((melee.damage, melee.hit_bonus, melee.damage_type));
+            }
+            if two_handed.get(item).is_some() {
+                two_handed_weapon = true;
+            }
+        }
+        if *slot == EquipmentSlot::Shield && two_handed_weapon {
+            description = "(two-handed weapon)".to_string();
+        }
+        ctx.print(30, y, description);
+    }
+
+    let player_attributes = attributes.get(*player_entity).cloned().unwrap_or_default();
+    let player_skills = skills.get(*player_entity).cloned().unwrap_or_default();
+    let y = y + count + 1;
+    ctx.print_color(
+        17,
+        y,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!(
+            "Armor class: {} ({:+} from gear)",
+            attributes::armor_class(&player_attributes, &player_skills) + gear_defense,
+            gear_defense
+        ),
+    );
+    if let Some((dice, hit_bonus, damage_type)) = weapon {
+        let damage = dice_label(
+            dice.n_dice,
+            dice.die_type,
+            dice.bonus + attributes::melee_damage_bonus(&player_attributes),
         );
-        ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
+        ctx.print_color(
+            17,
+            y + 1,
+            RGB::named(rltk::YELLOW),
             RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
+            format!(
+                "Melee: {:+} to hit, {} {:?}",
+                attributes::melee_to_hit(&player_attributes, &player_skills) + hit_bonus,
+                damage,
+                damage_type
+            ),
         );
-
-        ctx.print(21, y, &name.name.to_string());
-
-        equippable.push(entity);
-        y += 1;
-        j += 1;
     }
+    ctx.print_color(
+        17,
+        y + 3,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        format!(
+            "Mig {}  Fit {}  Qui {}  Int {}",
+            player_attributes.might,
+            player_attributes.fitness,
+            player_attributes.quickness,
+            player_attributes.intelligence
+        ),
+    );
+    ctx.print_color(
+        17,
+        y + 4,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        format!(
+            "Melee {}  Defense {}  Magic {}  Traps {}  Stealth {}",
+            player_skills.melee,
+            player_skills.defense,
+            player_skills.magic,
+            player_skills.traps,
+            player_skills.stealth
+        ),
+    );
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
     }
 }
 
@@ -338,11 +1158,35 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
     );
 
     match ctx.key {
-        None => GameOverResult::NoSelection,
-        Some(key) => match key {
-            VirtualKeyCode::Return => GameOverResult::QuitToMenu,
-            _ => GameOverResult::NoSelection,
-        },
+        Some(VirtualKeyCode::Return) => GameOverResult::QuitToMenu,
+        _ => GameOverResult::NoSelection,
+    }
+}
+
+pub fn victory(ctx: &mut Rltk) -> GameOverResult {
+    ctx.print_color_centered(
+        15,
+        RGB::named(rltk::GOLD),
+        RGB::named(rltk::BLACK),
+        "You have escaped the dungeon with the Amulet of Ages!",
+    );
+    ctx.print_color_centered(
+        17,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        "Songs will be sung of your deeds for generations.",
+    );
+
+    ctx.print_color_centered(
+        20,
+        RGB::named(rltk::MAGENTA),
+        RGB::named(rltk::BLACK),
+        "Press Enter to return to the menu.",
+    );
+
+    match ctx.key {
+        Some(VirtualKeyCode::Return) => GameOverResult::QuitToMenu,
+        _ => GameOverResult::NoSelection,
     }
 }
 
@@ -367,9 +1211,10 @@ pub fn draw_ui(world: &World, ctx: &mut Rltk) {
     );
 
     let combat_stats = world.read_storage::<CombatStats>();
+    let mana = world.read_storage::<Mana>();
     let players = world.read_storage::<Player>();
     let hunger_clock = world.read_storage::<HungerClock>();
-    for (_player, stats, hunger) in (&players, &combat_stats, &hunger_clock).join() {
+    for (_player, stats, hunger, mana) in (&players, &combat_stats, &hunger_clock, &mana).join() {
         let health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
         ctx.print_color(
             12,
@@ -381,13 +1226,31 @@ pub fn draw_ui(world: &World, ctx: &mut Rltk) {
         ctx.draw_bar_horizontal(
             28,
             43,
-            51,
+            20,
             stats.hp,
             stats.max_hp,
             RGB::named(rltk::RED),
             RGB::named(rltk::BLACK),
         );
 
+        let mana_text = format!(" MP: {} / {} ", mana.current, mana.max);
+        ctx.print_color(
+            50,
+            43,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            mana_text,
+        );
+        ctx.draw_bar_horizontal(
+            64,
+            43,
+            14,
+            mana.current,
+            mana.max,
+            RGB::named(rltk::BLUE),
+            RGB::named(rltk::BLACK),
+        );
+
         match hunger.state {
             HungerState::WellFed => ctx.print_color(
                 71,
@@ -414,13 +1277,75 @@ pub fn draw_ui(world: &World, ctx: &mut Rltk) {
         }
     }
 
+    let player_entity = world.fetch::<Entity>();
+    if let Some(wallet) = world.read_storage::<Wallet>().get(*player_entity) {
+        ctx.print_color(
+            44,
+            42,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            format!("Gold: {}", wallet.gold),
+        );
+    }
+    if world
+        .read_storage::<Overburdened>()
+        .get(*player_entity)
+        .is_some()
+    {
+        ctx.print_color(
+            57,
+            42,
+            RGB::named(rltk::ORANGE),
+            RGB::named(rltk::BLACK),
+            "Overburdened",
+        );
+    }
+
+    let status_effects = world.read_storage::<StatusEffects>();
+    if let Some(statuses) = status_effects.get(*player_entity) {
+        let mut x = 1;
+        for status in statuses.effects.iter() {
+            let (glyph, color) = status_icon(status.effect);
+            let turns = status.turns.to_string();
+            ctx.set(x, 42, RGB::named(color), RGB::named(rltk::BLACK), glyph);
+            ctx.print_color(
+                x + 1,
+                42,
+                RGB::named(color),
+                RGB::named(rltk::BLACK),
+                &turns,
+            );
+            x += turns.len() as i32 + 2;
+        }
+    }
+
+    let experience = world.read_storage::<Experience>();
+    if let Some(experience) = experience.get(*player_entity) {
+        let next_level = attributes::xp_to_next_level(experience.level);
+        let xp = format!(
+            " Level {}  XP: {} / {} ",
+            experience.level, experience.xp, next_level
+        );
+        ctx.print_color(2, 49, RGB::named(rltk::GOLD), RGB::named(rltk::BLACK), xp);
+        ctx.draw_bar_horizontal(
+            28,
+            49,
+            50,
+            experience.xp,
+            next_level,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+        );
+    }
+
+    draw_spell_hotbar(world, ctx);
+
     let log = world.read_resource::<GameLog>();
-    let mut y = 44;
-    for entry in log.entries.iter().rev() {
+    for (i, entry) in log.entries.iter().rev().enumerate() {
+        let y = 44 + i as i32;
         if y < 49 {
             ctx.print(2, y, entry);
         }
-        y += 1;
     }
 
     let mouse_pos = ctx.mouse_pos();
@@ -428,11 +1353,57 @@ pub fn draw_ui(world: &World, ctx: &mut Rltk) {
     draw_tooltips(world, ctx);
 }
 
+fn draw_spell_hotbar(world: &World, ctx: &mut Rltk) {
+    let player_entity = world.fetch::<Entity>();
+    let known_spells = world.read_storage::<KnownSpells>();
+    let templates = world.read_storage::<SpellTemplate>();
+    let current_mana = world
+        .read_storage::<Mana>()
+        .get(*player_entity)
+        .map_or(0, |mana| mana.current);
+    let known = match known_spells.get(*player_entity) {
+        None => return,
+        Some(known) => known,
+    };
+
+    let mut x = 21;
+    for (i, name) in known.spells.iter().enumerate() {
+        let mana_cost = spells::find_spell(world, name)
+            .and_then(|spell| templates.get(spell))
+            .map_or(0, |template| template.mana_cost);
+        let fg = if current_mana >= mana_cost {
+            RGB::named(rltk::CYAN)
+        } else {
+            RGB::named(rltk::GRAY)
+        };
+        let label = format!("{}:{} ({})", i + 1, name, mana_cost);
+        ctx.print_color(x, 0, fg, RGB::named(rltk::BLACK), &label);
+        x += label.len() as i32 + 1;
+    }
+}
+
+fn status_icon(effect: StatusEffectType) -> (rltk::FontCharType, (u8, u8, u8)) {
+    match effect {
+        StatusEffectType::Poison => (rltk::to_cp437('♣'), rltk::GREEN),
+        StatusEffectType::Slow => (rltk::to_cp437('▼'), rltk::LIGHT_BLUE),
+        StatusEffectType::Haste => (rltk::to_cp437('▲'), rltk::YELLOW),
+        StatusEffectType::Paralysis => (rltk::to_cp437('■'), rltk::GREY),
+        StatusEffectType::Regeneration => (rltk::to_cp437('♥'), rltk::RED),
+        StatusEffectType::Blindness => (rltk::to_cp437('○'), rltk::DARK_GREY),
+        StatusEffectType::Confusion => (rltk::to_cp437('?'), rltk::MAGENTA),
+    }
+}
+
 fn draw_tooltips(world: &World, ctx: &mut Rltk) {
     let map = world.fetch::<Map>();
     let names = world.read_storage::<Name>();
     let positions = world.read_storage::<Position>();
     let hidden = world.read_storage::<Hidden>();
+    let monsters = world.read_storage::<Monster>();
+    let alerted = world.read_storage::<Alerted>();
+    let asleep = world.read_storage::<Asleep>();
+    let item_names = world.fetch::<ItemNameTable>();
+    let player_pos = *world.fetch::<Point>();
 
     let mouse_pos = ctx.mouse_pos();
     if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
@@ -440,10 +1411,32 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk) {
     }
 
     let mut tooltip = Vec::<String>::new();
-    for (name, position, _hidden) in (&names, &positions, &hidden).join() {
-        let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+    for (entity, name, position, _hidden) in
+        (&world.entities(), &names, &positions, !&hidden).join()
+    {
+        if position.x == mouse_pos.0
+            && position.y == mouse_pos.1
+            && lighting::can_see_at(&map, player_pos, position.x, position.y)
+        {
+            let display_name = item_names.display_name(&name.name);
+            if asleep.get(entity).is_some() {
+                tooltip.push(format!("{} (asleep)", display_name));
+            } else if monsters.get(entity).is_some() && alerted.get(entity).is_none() {
+                tooltip.push(format!("{} (unaware)", display_name));
+            } else {
+                tooltip.push(display_name);
+            }
+        }
+    }
+    if !lighting::can_see_at(&map, player_pos, mouse_pos.0, mouse_pos.1) {
+        let idx = map.xy_idx(mouse_pos.0, mouse_pos.1);
+        if let Some(memory) = map.remembered.get(&idx) {
+            let display_name = item_names.display_name(&memory.name);
+            if memory.monster {
+                tooltip.push(format!("{} (last seen)", display_name));
+            } else {
+                tooltip.push(format!("{} (remembered)", display_name));
+            }
         }
     }
 
@@ -459,8 +1452,8 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for t in tooltip.iter() {
+            for (i, t) in tooltip.iter().enumerate() {
+                let y = mouse_pos.1 + i as i32;
                 ctx.print_color(
                     left_x,
                     y,
@@ -475,23 +1468,22 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ".to_string(),
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"->".to_string(),
+                "->".to_string(),
             );
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for t in tooltip.iter() {
+            for (i, t) in tooltip.iter().enumerate() {
+                let y = mouse_pos.1 + i as i32;
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -506,17 +1498,16 @@ fn draw_tooltips(world: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ".to_string(),
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"<-".to_string(),
+                "<-".to_string(),
             );
         }
     }
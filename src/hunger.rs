@@ -1,7 +1,8 @@
 use specs::prelude::*;
 
 use crate::{
-    components::{HungerClock, SuffersDamage},
+    components::{DamageType, HungerClock},
+    effects::{EffectType, EffectsQueue, Targets},
     gamelog::GameLog,
     state::RunState,
 };
@@ -15,15 +16,14 @@ impl<'a> System<'a> for HungerSystem {
         WriteStorage<'a, HungerClock>,
         ReadExpect<'a, Entity>,
         ReadExpect<'a, RunState>,
-        WriteStorage<'a, SuffersDamage>,
+        WriteExpect<'a, EffectsQueue>,
         WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, runstate, mut inflict_damage, mut log) =
-            data;
+        let (entities, mut hunger_clock, player_entity, runstate, mut effects, mut log) = data;
 
-        for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
+        for (entity, clock) in (&entities, &mut hunger_clock).join() {
             let mut proceed = false;
 
             match *runstate {
@@ -69,7 +69,14 @@ impl<'a> System<'a> for HungerSystem {
                             if entity == *player_entity {
                                 log.entries.push("Your hunger pangs are getting painful! You suffer 1 hp damage.".to_string());
                             }
-                            SuffersDamage::new_damage(&mut inflict_damage, entity, 1);
+                            effects.add_effect(
+                                None,
+                                EffectType::Damage {
+                                    amount: 1,
+                                    damage_type: DamageType::Hunger,
+                                },
+                                Targets::Single { target: entity },
+                            );
                         }
                     }
                 }
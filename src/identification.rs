@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::{
+    components::{ItemCategory, Name, Stackable},
+    gamelog::GameLog,
+};
+
+const SCROLL_SYLLABLES: [&str; 14] = [
+    "ZAP", "PO", "FLIM", "KLAA", "TU", "NOR", "XI", "BAR", "GLO", "MOK", "VEN", "RHA", "DOO", "SEL",
+];
+const POTION_ADJECTIVES: [&str; 9] = [
+    "murky",
+    "bubbling",
+    "fizzy",
+    "cloudy",
+    "glowing",
+    "oily",
+    "smoky",
+    "viscous",
+    "sparkling",
+];
+const POTION_COLOURS: [&str; 8] = [
+    "red", "blue", "green", "amber", "violet", "black", "silver", "pink",
+];
+
+/// The per-run mapping from an item's true name to the name it goes by until identified.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ItemNameTable {
+    obfuscated: HashMap<String, String>,
+    identified: HashSet<String>,
+}
+
+impl ItemNameTable {
+    pub fn new() -> ItemNameTable {
+        ItemNameTable::default()
+    }
+
+    pub fn register(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        name: &str,
+        category: ItemCategory,
+    ) {
+        if self.obfuscated.contains_key(name) {
+            return;
+        }
+        loop {
+            let candidate = match category {
+                ItemCategory::Potion => potion_name(rng),
+                _ => scroll_name(rng),
+            };
+            if !self.obfuscated.values().any(|taken| *taken == candidate) {
+                self.obfuscated.insert(name.to_string(), candidate);
+                return;
+            }
+        }
+    }
+
+    pub fn is_identified(&self, name: &str) -> bool {
+        !self.obfuscated.contains_key(name) || self.identified.contains(name)
+    }
+
+    pub fn display_name(&self, name: &str) -> String {
+        match self.obfuscated.get(name) {
+            Some(obfuscated) if !self.identified.contains(name) => obfuscated.clone(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Returns the name the item went by if this identified it for the first time.
+    pub fn identify(&mut self, name: &str) -> Option<String> {
+        if self.is_identified(name) {
+            return None;
+        }
+        let previous = self.display_name(name);
+        self.identified.insert(name.to_string());
+        Some(previous)
+    }
+}
+
+fn scroll_name(rng: &mut RandomNumberGenerator) -> String {
+    let mut word = || {
+        let first = rng.random_slice_entry(&SCROLL_SYLLABLES).unwrap();
+        let second = rng.random_slice_entry(&SCROLL_SYLLABLES).unwrap();
+        format!("{}{}", first, second)
+    };
+    format!("scroll of {} {}", word(), word())
+}
+
+fn potion_name(rng: &mut RandomNumberGenerator) -> String {
+    format!(
+        "{} {} potion",
+        rng.random_slice_entry(&POTION_ADJECTIVES).unwrap(),
+        rng.random_slice_entry(&POTION_COLOURS).unwrap()
+    )
+}
+
+/// Reading an identify scroll on `target`: reveals its true name and uses up the scroll.
+pub fn identify_with_scroll(world: &mut World, scroll: Entity, target: Entity) {
+    let target_name = world
+        .read_storage::<Name>()
+        .get(target)
+        .unwrap()
+        .name
+        .clone();
+    let scroll_name = world
+        .read_storage::<Name>()
+        .get(scroll)
+        .unwrap()
+        .name
+        .clone();
+    {
+        let mut table = world.write_resource::<ItemNameTable>();
+        let mut log = world.write_resource::<GameLog>();
+        table.identify(&scroll_name);
+        match table.identify(&target_name) {
+            Some(previous) => log
+                .entries
+                .push(format!("The {} is a {}!", previous, target_name)),
+            None => log
+                .entries
+                .push(format!("You already know the {}.", target_name)),
+        }
+    }
+
+    let mut stackables = world.write_storage::<Stackable>();
+    match stackables.get_mut(scroll) {
+        Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+        _ => {
+            drop(stackables);
+            world.delete_entity(scroll).expect("Delete failed");
+        }
+    }
+}
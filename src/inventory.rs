@@ -3,49 +3,117 @@ use specs::prelude::*;
 
 use crate::{
     components::{
-        AreaOfEffect, CombatStats, Confusion, Consumable, Equippable, Equipped, HungerClock,
-        HungerState, InBackpack, InflictsDamage, MagicMapper, Name, Position, ProvidesFood,
-        ProvidesHealing, SuffersDamage, WantsToDropItem, WantsToPickUpItem, WantsToRemoveItem,
-        WantsToUseItem,
+        AreaOfEffect, Charges, Consumable, Cursed, EquipmentSlot, Equippable, Equipped, Gold,
+        HungerClock, HungerState, InBackpack, InflictsDamage, InflictsStatus, KnownSpells,
+        MagicMapper, Name, Position, ProvidesFood, ProvidesHealing, RemovesCurse, Resistances,
+        Stackable, TeachesSpell, Teleports, TwoHanded, Wallet, WantsToDropItem, WantsToPickUpItem,
+        WantsToRemoveItem, WantsToUseItem,
     },
+    damage,
+    effects::{targeted_entities, EffectType, EffectsQueue, Targets},
     gamelog::GameLog,
+    identification::ItemNameTable,
     map::Map,
-    particles::ParticlesBuilder,
+    spawner,
     state::RunState,
+    status_effects,
 };
 
 pub struct ItemColecctionSystem {}
 
 impl<'a> System<'a> for ItemColecctionSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
+        Entities<'a>,
         WriteStorage<'a, WantsToPickUpItem>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        ReadExpect<'a, ItemNameTable>,
+        ReadStorage<'a, Gold>,
+        WriteStorage<'a, Wallet>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            mut stackables,
+            item_names,
+            gold,
+            mut wallets,
+        ) = data;
 
         for pickup in wants_pickup.join() {
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
+            if let Some(coins) = gold.get(pickup.item) {
+                if let Some(wallet) = wallets.get_mut(pickup.collected_by) {
+                    wallet.gold += coins.amount;
+                }
+                entities.delete(pickup.item).expect("Delete failed");
+                if pickup.collected_by == *player_entity {
+                    gamelog
+                        .entries
+                        .push(format!("You pick up {} gold.", coins.amount));
+                }
+                continue;
+            }
 
-            if pickup.collected_by == *player_entity {
-                gamelog.entries.push(format!(
-                    "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name
-                ))
+            let item_name = &names.get(pickup.item).unwrap().name;
+
+            let existing_stack = if stackables.get(pickup.item).is_some() {
+                (&entities, &backpack, &names, &stackables)
+                    .join()
+                    .find(|(entity, carried_by, name, _stack)| {
+                        *entity != pickup.item
+                            && carried_by.owner == pickup.collected_by
+                            && name.name == *item_name
+                    })
+                    .map(|(entity, ..)| entity)
+            } else {
+                None
+            };
+
+            match existing_stack {
+                Some(stack) => {
+                    let quantity = stackables.get(pickup.item).unwrap().quantity;
+                    let merged = stackables.get_mut(stack).unwrap();
+                    merged.quantity += quantity;
+                    let total = merged.quantity;
+                    entities.delete(pickup.item).expect("Delete failed");
+                    if pickup.collected_by == *player_entity {
+                        gamelog.entries.push(format!(
+                            "You pick up the {} (now x{}).",
+                            item_names.display_name(item_name),
+                            total
+                        ));
+                    }
+                }
+                None => {
+                    backpack
+                        .insert(
+                            pickup.item,
+                            InBackpack {
+                                owner: pickup.collected_by,
+                            },
+                        )
+                        .expect("Unable to insert backpack entry");
+
+                    if pickup.collected_by == *player_entity {
+                        gamelog.entries.push(format!(
+                            "You pick up the {}.",
+                            item_names.display_name(item_name)
+                        ))
+                    }
+                }
             }
         }
 
@@ -66,20 +134,24 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, Consumable>,
+        WriteStorage<'a, Stackable>,
         ReadStorage<'a, ProvidesHealing>,
         ReadStorage<'a, InflictsDamage>,
-        WriteStorage<'a, SuffersDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
-        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, InflictsStatus>,
+        ReadStorage<'a, Teleports>,
         ReadStorage<'a, Equippable>,
+        ReadStorage<'a, TwoHanded>,
         WriteStorage<'a, Equipped>,
         ReadStorage<'a, ProvidesFood>,
         WriteStorage<'a, HungerClock>,
         ReadStorage<'a, MagicMapper>,
         WriteStorage<'a, InBackpack>,
-        WriteExpect<'a, ParticlesBuilder>,
-        ReadStorage<'a, Position>,
+        WriteExpect<'a, EffectsQueue>,
+        WriteExpect<'a, ItemNameTable>,
+        (WriteStorage<'a, Cursed>, ReadStorage<'a, RemovesCurse>),
+        (ReadStorage<'a, TeachesSpell>, WriteStorage<'a, KnownSpells>),
+        (WriteStorage<'a, Charges>, ReadStorage<'a, Resistances>),
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -92,36 +164,119 @@ impl<'a> System<'a> for ItemUseSystem {
             mut wants_use,
             names,
             consumables,
+            mut stackables,
             healing,
             inflicts_damage,
-            mut suffers_damage,
             area_of_effect,
-            mut confused,
-            mut combat_stats,
+            inflicts_status,
+            teleports,
             equippable,
+            two_handed,
             mut equipped,
             provides_food,
             mut hunger_clocks,
             magic_mappers,
             mut backpack,
-            mut particles_builder,
-            positions,
+            mut effects,
+            mut item_names,
+            (mut cursed, removes_curse),
+            (teaches_spell, mut known_spells),
+            (mut charges, resistances),
         ) = data;
 
-        for (entity, use_item, stats) in (&entities, &wants_use, &mut combat_stats).join() {
+        for (entity, use_item) in (&entities, &wants_use).join() {
+            if let Some(item_charges) = charges.get_mut(use_item.item) {
+                if item_charges.current < 1 {
+                    if entity == *player_entity {
+                        gamelog.entries.push(format!(
+                            "The {} is out of charges.",
+                            names.get(use_item.item).unwrap().name
+                        ));
+                    }
+                    continue;
+                }
+                item_charges.current -= 1;
+            }
+
+            let true_name = &names.get(use_item.item).unwrap().name;
+            if let Some(previous) = item_names.identify(true_name) {
+                if entity == *player_entity {
+                    gamelog
+                        .entries
+                        .push(format!("The {} was a {}!", previous, true_name));
+                }
+            }
+
             let consumable = consumables.get(use_item.item);
             match consumable {
                 None => {}
-                Some(_) => {
-                    entities.delete(use_item.item).expect("Delete failed");
-                }
+                Some(_) => match stackables.get_mut(use_item.item) {
+                    Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+                    _ => entities.delete(use_item.item).expect("Delete failed"),
+                },
             }
 
+            let targets = match use_item.target {
+                None => Targets::Single { target: entity },
+                Some(target) => match area_of_effect.get(use_item.item) {
+                    None => Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y),
+                    },
+                    Some(area_effect) => {
+                        let mut blast_tiles =
+                            rltk::field_of_view(target, area_effect.radius, &*map);
+                        blast_tiles.retain(|p| {
+                            p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
+                        });
+                        let tiles: Vec<usize> =
+                            blast_tiles.iter().map(|p| map.xy_idx(p.x, p.y)).collect();
+
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('░'),
+                                fg: RGB::named(rltk::ORANGE),
+                                bg: RGB::named(rltk::BLACK),
+                                lifetime_ms: 200.0,
+                            },
+                            Targets::Area {
+                                tiles: tiles.clone(),
+                            },
+                        );
+                        Targets::Area { tiles }
+                    }
+                },
+            };
+            // Spells and blasts light up where they land, if only for a moment.
+            if let Some(target) = use_item.target {
+                let range = area_of_effect
+                    .get(use_item.item)
+                    .map_or(1, |area_effect| area_effect.radius + 1);
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::Flash {
+                        colour: RGB::named(rltk::ORANGE),
+                        range,
+                        lifetime_ms: 200.0,
+                    },
+                    Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y),
+                    },
+                );
+            }
+            let mobs = targeted_entities(&map, &targets);
+
             let item_heals = healing.get(use_item.item);
             match item_heals {
                 None => {}
                 Some(healer) => {
-                    stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Healing {
+                            amount: healer.heal_amount,
+                        },
+                        targets.clone(),
+                    );
                     if entity == *player_entity {
                         gamelog.entries.push(format!(
                             "You drink the {}, healing {} hp.",
@@ -129,17 +284,6 @@ impl<'a> System<'a> for ItemUseSystem {
                             healer.heal_amount
                         ));
                     }
-
-                    if let Some(pos) = positions.get(entity) {
-                        particles_builder.request(
-                            pos.x,
-                            pos.y,
-                            RGB::named(rltk::GREEN),
-                            RGB::named(rltk::BLACK),
-                            rltk::to_cp437('♥'),
-                            200.0,
-                        )
-                    }
                 }
             }
 
@@ -147,133 +291,145 @@ impl<'a> System<'a> for ItemUseSystem {
             match item_damage {
                 None => {}
                 Some(damage) => {
-                    let target_point = use_item.target.unwrap();
-                    let idx = map.xy_idx(target_point.x, target_point.y);
-                    for mob in map.tile_content[idx].iter() {
-                        SuffersDamage::new_damage(&mut suffers_damage, *mob, damage.damage);
-                        if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(use_item.item).unwrap();
-                            gamelog.entries.push(format!(
-                                "you use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
-                            ));
-
-                            if let Some(pos) = positions.get(*mob) {
-                                particles_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    rltk::RGB::named(rltk::RED),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('‼'),
-                                    200.0,
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Damage {
+                            amount: damage.damage,
+                            damage_type: damage.damage_type,
+                        },
+                        targets.clone(),
+                    );
+                    if entity == *player_entity {
+                        let item_name = names.get(use_item.item).unwrap();
+                        for mob in mobs.iter() {
+                            if let Some(mob_name) = names.get(*mob) {
+                                let dealt = damage::resolved_damage(
+                                    *mob,
+                                    damage.damage,
+                                    damage.damage_type,
+                                    &resistances,
+                                    &equipped,
                                 );
+                                gamelog.entries.push(format!(
+                                    "you use {} on {}, inflicting {} hp.",
+                                    item_name.name, mob_name.name, dealt
+                                ));
                             }
                         }
                     }
                 }
             }
 
-            let mut targets: Vec<Entity> = Vec::new();
-            match use_item.target {
-                None => {
-                    targets.push(*player_entity);
-                }
-                Some(target) => {
-                    let area_effect = area_of_effect.get(use_item.item);
-                    match area_effect {
-                        None => {
-                            let idx = map.xy_idx(target.x, target.y);
-                            for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
-                            }
-                        }
-                        Some(area_effect) => {
-                            let mut blast_tiles =
-                                rltk::field_of_view(target, area_effect.radius, &*map);
-                            blast_tiles.retain(|p| {
-                                p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
-                            });
-                            for tile_idx in blast_tiles.iter() {
-                                let idx = map.xy_idx(tile_idx.x, tile_idx.y);
-                                for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
-                                }
-
-                                particles_builder.request(
-                                    tile_idx.x,
-                                    tile_idx.y,
-                                    rltk::RGB::named(rltk::ORANGE),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('░'),
-                                    200.0,
-                                );
+            let item_status = inflicts_status.get(use_item.item);
+            match item_status {
+                None => {}
+                Some(status) => {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Status {
+                            effect: status.effect,
+                            turns: status.turns,
+                        },
+                        targets.clone(),
+                    );
+                    if entity == *player_entity {
+                        let item_name = names.get(use_item.item).unwrap();
+                        for mob in mobs.iter() {
+                            if let Some(mob_name) = names.get(*mob) {
+                                gamelog.entries.push(format!(
+                                    "You use {} on {}, leaving them {}.",
+                                    item_name.name,
+                                    mob_name.name,
+                                    status_effects::adjective(status.effect)
+                                ));
                             }
                         }
                     }
                 }
             }
 
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(use_item.item);
-                match causes_confusion {
-                    None => {}
-                    Some(confusion) => {
-                        for mob in targets.iter() {
-                            add_confusion.push((*mob, confusion.turns));
+            let item_teleports = teleports.get(use_item.item);
+            match item_teleports {
+                None => {}
+                Some(_) => {
+                    if let Some(target) = use_item.target {
+                        let idx = map.xy_idx(target.x, target.y);
+                        if map.blocked[idx] {
                             if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(use_item.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
-                                ));
-
-                                if let Some(pos) = positions.get(*mob) {
-                                    particles_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::MAGENTA),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('?'),
-                                        200.0,
-                                    );
-                                }
+                                gamelog
+                                    .entries
+                                    .push("The magic fizzles against something solid.".to_string());
+                            }
+                        } else {
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Teleport {
+                                    x: target.x,
+                                    y: target.y,
+                                },
+                                Targets::Single { target: entity },
+                            );
+                            if entity == *player_entity {
+                                gamelog
+                                    .entries
+                                    .push("You blink across the room.".to_string());
                             }
                         }
                     }
                 }
             }
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
-            }
 
             let item_equippable = equippable.get(use_item.item);
             match item_equippable {
                 None => {}
                 Some(can_equip) => {
-                    let target_slot = can_equip.slot;
-                    let target = targets[0];
+                    let mut target_slot = can_equip.slot;
+                    if target_slot == EquipmentSlot::LeftRing {
+                        let slot_taken = |slot| {
+                            (&equipped)
+                                .join()
+                                .any(|worn| worn.owner == entity && worn.slot == slot)
+                        };
+                        if slot_taken(EquipmentSlot::LeftRing)
+                            && !slot_taken(EquipmentSlot::RightRing)
+                        {
+                            target_slot = EquipmentSlot::RightRing;
+                        }
+                    }
+                    let needs_both_hands = two_handed.get(use_item.item).is_some();
 
                     let mut to_unequip: Vec<Entity> = Vec::new();
-                    for (item_entity, already_equipped, name) in
-                        (&entities, &equipped, &names).join()
-                    {
-                        if already_equipped.owner == target && already_equipped.slot == target_slot
-                        {
+                    for (item_entity, already_equipped) in (&entities, &equipped).join() {
+                        if already_equipped.owner != entity {
+                            continue;
+                        }
+                        let blocks_shield = target_slot == EquipmentSlot::Shield
+                            && two_handed.get(item_entity).is_some();
+                        let blocks_weapon =
+                            needs_both_hands && already_equipped.slot == EquipmentSlot::Shield;
+                        if already_equipped.slot == target_slot || blocks_shield || blocks_weapon {
                             to_unequip.push(item_entity);
-                            if target == *player_entity {
-                                gamelog.entries.push(format!("you unequip {}.", name.name));
-                            }
                         }
                     }
+                    if let Some(stuck) = to_unequip.iter().find(|item| cursed.get(**item).is_some())
+                    {
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You cannot remove the {}, it is cursed!",
+                                names.get(*stuck).unwrap().name
+                            ));
+                        }
+                        continue;
+                    }
                     for item in to_unequip.iter() {
+                        if entity == *player_entity {
+                            gamelog
+                                .entries
+                                .push(format!("you unequip {}.", names.get(*item).unwrap().name));
+                        }
                         equipped.remove(*item);
                         backpack
-                            .insert(*item, InBackpack { owner: target })
+                            .insert(*item, InBackpack { owner: entity })
                             .expect("Unable to insert backpack entry");
                     }
 
@@ -281,17 +437,62 @@ impl<'a> System<'a> for ItemUseSystem {
                         .insert(
                             use_item.item,
                             Equipped {
-                                owner: target,
+                                owner: entity,
                                 slot: target_slot,
                             },
                         )
                         .expect("Unable to insert equipped component");
                     backpack.remove(use_item.item);
-                    if target == *player_entity {
+                    if entity == *player_entity {
                         gamelog.entries.push(format!(
                             "You equip {}.",
                             names.get(use_item.item).unwrap().name
                         ));
+                        if cursed.get(use_item.item).is_some() {
+                            gamelog
+                                .entries
+                                .push("It clings to you with a malign chill. Cursed!".to_string());
+                        }
+                    }
+                }
+            }
+
+            if removes_curse.get(use_item.item).is_some() {
+                let lifted: Vec<Entity> = (&entities, &equipped)
+                    .join()
+                    .filter(|(item, worn)| worn.owner == entity && cursed.get(*item).is_some())
+                    .map(|(item, _worn)| item)
+                    .collect();
+                for item in lifted.iter() {
+                    cursed.remove(*item);
+                }
+                if entity == *player_entity {
+                    if lifted.is_empty() {
+                        gamelog
+                            .entries
+                            .push("You feel a brief sense of relief.".to_string());
+                    } else {
+                        gamelog
+                            .entries
+                            .push("A malevolent weight lifts from your gear.".to_string());
+                    }
+                }
+            }
+
+            if let Some(teaches) = teaches_spell.get(use_item.item) {
+                if let Some(known) = known_spells.get_mut(entity) {
+                    let message = if known.spells.contains(&teaches.spell) {
+                        format!("You already know {}.", teaches.spell)
+                    } else {
+                        known.spells.push(teaches.spell.clone());
+                        format!(
+                            "You learn {}! Press {} to cast it.",
+                            teaches.spell,
+                            known.spells.len()
+                        )
+                    };
+                    if entity == *player_entity {
+                        gamelog.entries.push(message);
                     }
                 }
             }
@@ -300,8 +501,7 @@ impl<'a> System<'a> for ItemUseSystem {
             match item_edible {
                 None => {}
                 Some(_) => {
-                    let target = targets[0];
-                    if let Some(hunger_clock) = hunger_clocks.get_mut(target) {
+                    if let Some(hunger_clock) = hunger_clocks.get_mut(entity) {
                         hunger_clock.state = HungerState::WellFed;
                         hunger_clock.duration = 200;
                         gamelog.entries.push(format!(
@@ -343,6 +543,9 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        Read<'a, LazyUpdate>,
+        ReadExpect<'a, ItemNameTable>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -354,6 +557,9 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            mut stackables,
+            lazy,
+            item_names,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -364,21 +570,36 @@ impl<'a> System<'a> for ItemDropSystem {
                 dropper_pos.y = dropped_pos.y;
             }
 
-            positions
-                .insert(
-                    to_drop.item,
-                    Position {
-                        x: dropper_pos.x,
-                        y: dropper_pos.y,
-                    },
-                )
-                .expect("Unable to insert position");
-            backpack.remove(to_drop.item);
+            match stackables.get_mut(to_drop.item) {
+                Some(stack) if stack.quantity > 1 => {
+                    stack.quantity -= 1;
+                    let item = to_drop.item;
+                    lazy.exec_mut(move |world| {
+                        let single = split_stack(world, item);
+                        world
+                            .write_storage::<Position>()
+                            .insert(single, dropper_pos)
+                            .expect("Unable to insert position");
+                    });
+                }
+                _ => {
+                    positions
+                        .insert(
+                            to_drop.item,
+                            Position {
+                                x: dropper_pos.x,
+                                y: dropper_pos.y,
+                            },
+                        )
+                        .expect("Unable to insert position");
+                    backpack.remove(to_drop.item);
+                }
+            }
 
             if entity == *player_entity {
                 gamelog.entries.push(format!(
                     "You drop the {}.",
-                    names.get(to_drop.item).unwrap().name
+                    item_names.display_name(&names.get(to_drop.item).unwrap().name)
                 ));
             }
         }
@@ -387,21 +608,50 @@ impl<'a> System<'a> for ItemDropSystem {
     }
 }
 
+/// Creates a lone copy of a stacked item, leaving the original stack untouched. The copy is
+/// spawned afresh from the item's name, so it is made of exactly what the spawner gives it.
+pub fn split_stack(world: &mut World, item: Entity) -> Entity {
+    let name = world.read_storage::<Name>().get(item).unwrap().name.clone();
+    spawner::spawn_copy(world, &name).expect("Stacked items must spawn by name")
+}
+
 pub struct ItemRemoveSystem {}
 
 impl<'a> System<'a> for ItemRemoveSystem {
-    #[allow(clippy::clippy::type_complexity)]
+    #[allow(clippy::type_complexity)]
     type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
         Entities<'a>,
         WriteStorage<'a, WantsToRemoveItem>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Name>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack) = data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            mut equipped,
+            mut backpack,
+            cursed,
+            names,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if cursed.get(to_remove.item).is_some() {
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You cannot remove the {}, it is cursed!",
+                        names.get(to_remove.item).unwrap().name
+                    ));
+                }
+                continue;
+            }
             equipped.remove(to_remove.item);
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
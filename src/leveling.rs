@@ -0,0 +1,75 @@
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{Attributes, CombatStats, Experience, Mana, Skills},
+    gamelog::GameLog,
+};
+
+pub const LEVEL_UP_CHOICES: [&str; 9] = [
+    "Might",
+    "Fitness",
+    "Quickness",
+    "Intelligence",
+    "Melee skill",
+    "Defense skill",
+    "Magic skill",
+    "Traps skill",
+    "Stealth skill",
+];
+
+pub fn can_level_up(world: &World) -> bool {
+    let player_entity = world.fetch::<Entity>();
+    let experience = world.read_storage::<Experience>();
+    match experience.get(*player_entity) {
+        Some(experience) => experience.xp >= attributes::xp_to_next_level(experience.level),
+        None => false,
+    }
+}
+
+/// Advances the player one level, raising the chosen attribute or skill and granting
+/// the extra hit points and mana that the new level brings.
+pub fn level_up(world: &mut World, choice: usize) {
+    let player_entity = *world.fetch::<Entity>();
+    let mut experiences = world.write_storage::<Experience>();
+    let mut attributes = world.write_storage::<Attributes>();
+    let mut skills = world.write_storage::<Skills>();
+    let mut combat_stats = world.write_storage::<CombatStats>();
+    let mut mana = world.write_storage::<Mana>();
+    let mut log = world.write_resource::<GameLog>();
+
+    let experience = experiences.get_mut(player_entity).unwrap();
+    experience.xp -= attributes::xp_to_next_level(experience.level);
+    experience.level += 1;
+    let level = experience.level;
+
+    let player_attributes = attributes.get_mut(player_entity).unwrap();
+    let player_skills = skills.get_mut(player_entity).unwrap();
+    match choice {
+        0 => player_attributes.might += 1,
+        1 => player_attributes.fitness += 1,
+        2 => player_attributes.quickness += 1,
+        3 => player_attributes.intelligence += 1,
+        4 => player_skills.melee += 1,
+        5 => player_skills.defense += 1,
+        6 => player_skills.magic += 1,
+        7 => player_skills.traps += 1,
+        _ => player_skills.stealth += 1,
+    }
+
+    if let Some(stats) = combat_stats.get_mut(player_entity) {
+        let max_hp = attributes::player_max_hp(player_attributes, level);
+        stats.hp += max_hp - stats.max_hp;
+        stats.max_hp = max_hp;
+    }
+    if let Some(mana) = mana.get_mut(player_entity) {
+        mana.max = attributes::max_mana(player_attributes, player_skills, level);
+        mana.current = mana.max;
+    }
+
+    log.entries.push(format!(
+        "Welcome to level {}! Your {} improves.",
+        level,
+        LEVEL_UP_CHOICES[choice].to_lowercase()
+    ));
+}
@@ -0,0 +1,86 @@
+use rltk::{DistanceAlg, Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+use crate::{
+    components::{Equipped, InBackpack, LightSource, Position},
+    map::Map,
+};
+
+/// Levels from this depth down may be left unlit.
+const DARK_FROM_DEPTH: i32 = 4;
+/// One in this many eligible levels is dark.
+const DARK_LEVEL_CHANCE: i32 = 3;
+/// Light level of every tile on an ordinary level before any light sources are added.
+const DAYLIGHT: f32 = 0.85;
+/// A tile needs at least this much total light for anything on it to be seen.
+const VISIBLE_THRESHOLD: f32 = 0.2;
+
+pub fn roll_dark_level(rng: &mut RandomNumberGenerator, depth: i32) -> bool {
+    depth >= DARK_FROM_DEPTH && rng.roll_dice(1, DARK_LEVEL_CHANCE) == 1
+}
+
+pub fn is_lit(map: &Map, idx: usize) -> bool {
+    map.light
+        .get(idx)
+        .is_some_and(|light| light.r + light.g + light.b >= VISIBLE_THRESHOLD)
+}
+
+/// Things are seen on tiles in view that are lit, or close enough to touch.
+pub fn can_see_at(map: &Map, player: Point, x: i32, y: i32) -> bool {
+    let idx = map.xy_idx(x, y);
+    map.visible_tiles[idx]
+        && (is_lit(map, idx) || DistanceAlg::Pythagoras.distance2d(player, Point::new(x, y)) < 1.5)
+}
+
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        Entities<'a>,
+        ReadStorage<'a, LightSource>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, entities, light_sources, positions, backpack, equipped) = data;
+
+        let ambient = if map.dark {
+            RGB::from_f32(0., 0., 0.)
+        } else {
+            RGB::from_f32(DAYLIGHT, DAYLIGHT, DAYLIGHT)
+        };
+        map.light = vec![ambient; (map.width * map.height) as usize];
+
+        for (entity, light) in (&entities, &light_sources).join() {
+            // Carried lights shine from whoever holds them.
+            let holder = backpack
+                .get(entity)
+                .map(|pack| pack.owner)
+                .or_else(|| equipped.get(entity).map(|worn| worn.owner))
+                .unwrap_or(entity);
+            let pos = match positions.get(holder) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y),
+            };
+
+            let lit_tiles = rltk::field_of_view(pos, light.range, &*map);
+            for tile in lit_tiles.iter() {
+                if tile.x < 0 || tile.x >= map.width || tile.y < 0 || tile.y >= map.height {
+                    continue;
+                }
+                let distance = DistanceAlg::Pythagoras.distance2d(pos, *tile);
+                let intensity = f32::max(0., 1. - distance / (light.range as f32 + 1.));
+                let idx = map.xy_idx(tile.x, tile.y);
+                let mixed = map.light[idx] + light.colour * intensity;
+                map.light[idx] = RGB::from_f32(
+                    f32::min(1., mixed.r),
+                    f32::min(1., mixed.g),
+                    f32::min(1., mixed.b),
+                );
+            }
+        }
+    }
+}
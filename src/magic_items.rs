@@ -0,0 +1,150 @@
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::components::{
+    Cursed, DamageType, DefenseBonus, MagicItem, MagicItemClass, MeleeWeapon, Name,
+    ResistanceLevel, Resistances,
+};
+
+/// One in this many enchanted items rolls cursed, flipping its enchantment negative.
+const CURSE_CHANCE: i32 = 6;
+
+#[derive(Clone, Copy)]
+enum Affix {
+    Damage(DamageType),
+    HitBonus(i32),
+    Resist(DamageType),
+    Defense(i32),
+}
+
+const WEAPON_AFFIXES: [(&str, Affix); 3] = [
+    ("of Flame", Affix::Damage(DamageType::Fire)),
+    ("of Frost", Affix::Damage(DamageType::Cold)),
+    ("of Accuracy", Affix::HitBonus(2)),
+];
+
+const ARMOR_AFFIXES: [(&str, Affix); 3] = [
+    ("of Fire Warding", Affix::Resist(DamageType::Fire)),
+    ("of Frost Warding", Affix::Resist(DamageType::Cold)),
+    ("of the Turtle", Affix::Defense(1)),
+];
+
+fn roll_class(rng: &mut RandomNumberGenerator, map_depth: i32) -> MagicItemClass {
+    let roll = rng.roll_dice(1, 100) + map_depth * 3;
+    if roll > 105 {
+        MagicItemClass::Rare
+    } else if roll > 75 {
+        MagicItemClass::Magic
+    } else {
+        MagicItemClass::Common
+    }
+}
+
+/// Rolls a magic tier for freshly spawned equipment and applies its enchantment, affix and name.
+pub fn enchant(world: &mut World, item: Entity, map_depth: i32) {
+    roll_enchantment(world, item, map_depth, true);
+}
+
+/// As `enchant`, but never cursed: shopkeepers only stock what they would take back.
+pub fn enchant_uncursed(world: &mut World, item: Entity, map_depth: i32) {
+    roll_enchantment(world, item, map_depth, false);
+}
+
+fn roll_enchantment(world: &mut World, item: Entity, map_depth: i32, may_curse: bool) {
+    let is_weapon = world.read_storage::<MeleeWeapon>().get(item).is_some();
+    let is_armor = world.read_storage::<DefenseBonus>().get(item).is_some();
+    if !is_weapon && !is_armor {
+        return;
+    }
+
+    let (class, mut enchantment, cursed, affix) = {
+        let mut rng = world.write_resource::<RandomNumberGenerator>();
+        let class = roll_class(&mut rng, map_depth);
+        let enchantment = match class {
+            MagicItemClass::Common => return,
+            MagicItemClass::Magic => 1,
+            MagicItemClass::Rare => rng.roll_dice(1, 2) + 1,
+        };
+        let cursed = may_curse && rng.roll_dice(1, CURSE_CHANCE) == 1;
+        let affix = match class {
+            MagicItemClass::Rare if is_weapon => rng.random_slice_entry(&WEAPON_AFFIXES).copied(),
+            MagicItemClass::Rare => rng.random_slice_entry(&ARMOR_AFFIXES).copied(),
+            _ => None,
+        };
+        (class, enchantment, cursed, affix)
+    };
+    if cursed {
+        enchantment = -enchantment;
+    }
+
+    if let Some(weapon) = world.write_storage::<MeleeWeapon>().get_mut(item) {
+        weapon.hit_bonus += enchantment;
+        weapon.damage.bonus += enchantment;
+    }
+    if let Some(defense) = world.write_storage::<DefenseBonus>().get_mut(item) {
+        defense.defense += enchantment;
+    }
+    if let Some((_, affix)) = affix {
+        apply_affix(world, item, affix);
+    }
+
+    if let Some(name) = world.write_storage::<Name>().get_mut(item) {
+        let suffix = affix.map_or(String::new(), |(suffix, _)| format!(" {}", suffix));
+        // A curse only shows itself once the item is worn.
+        name.name = if cursed {
+            format!("{}{}", name.name, suffix)
+        } else {
+            format!("+{} {}{}", enchantment, name.name, suffix)
+        };
+    }
+
+    world
+        .write_storage::<MagicItem>()
+        .insert(item, MagicItem { class, enchantment })
+        .expect("Unable to insert magic item");
+    if cursed {
+        world
+            .write_storage::<Cursed>()
+            .insert(item, Cursed {})
+            .expect("Unable to insert curse");
+    }
+}
+
+fn apply_affix(world: &mut World, item: Entity, affix: Affix) {
+    match affix {
+        Affix::Damage(damage_type) => {
+            if let Some(weapon) = world.write_storage::<MeleeWeapon>().get_mut(item) {
+                weapon.damage_type = damage_type;
+            }
+        }
+        Affix::HitBonus(bonus) => {
+            if let Some(weapon) = world.write_storage::<MeleeWeapon>().get_mut(item) {
+                weapon.hit_bonus += bonus;
+            }
+        }
+        Affix::Defense(bonus) => {
+            if let Some(defense) = world.write_storage::<DefenseBonus>().get_mut(item) {
+                defense.defense += bonus;
+            }
+        }
+        Affix::Resist(damage_type) => {
+            let mut resistances = world.write_storage::<Resistances>();
+            match resistances.get_mut(item) {
+                Some(existing) if existing.get(damage_type).is_none() => existing
+                    .resistances
+                    .push((damage_type, ResistanceLevel::Resistant)),
+                Some(_) => {}
+                None => {
+                    resistances
+                        .insert(
+                            item,
+                            Resistances {
+                                resistances: vec![(damage_type, ResistanceLevel::Resistant)],
+                            },
+                        )
+                        .expect("Unable to insert resistances");
+                }
+            }
+        }
+    }
+}
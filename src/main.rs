@@ -23,16 +23,34 @@ use gamelog::GameLog;
 
 mod spawner;
 
+mod attributes;
+mod bosses;
+mod character_creation;
+mod charges;
 mod damage;
+mod effects;
+mod encumbrance;
 mod hunger;
+mod identification;
 mod inventory;
+mod leveling;
+mod lighting;
+mod magic_items;
 mod map_indexing;
 mod melee_combat;
+mod memory;
 mod monster_ai;
+mod noise;
 mod particles;
 mod saveload;
+mod spells;
+mod status_effects;
+mod throwing;
+mod traps;
 mod trigger;
+mod vendors;
 mod visibility;
+mod wandering;
 
 mod maps;
 mod random_table;
@@ -64,32 +82,68 @@ fn main() -> rltk::BError {
     game_state.world.register::<Viewshed>();
     game_state.world.register::<BlocksTile>();
     game_state.world.register::<CombatStats>();
+    game_state.world.register::<Attributes>();
+    game_state.world.register::<Skills>();
+    game_state.world.register::<Mana>();
+    game_state.world.register::<Wallet>();
+    game_state.world.register::<Gold>();
+    game_state.world.register::<Vendor>();
+    game_state.world.register::<LootTable>();
+    game_state.world.register::<LightSource>();
+    game_state.world.register::<Asleep>();
+    game_state.world.register::<Boss>();
+    game_state.world.register::<QuestItem>();
+    game_state.world.register::<KnownSpells>();
+    game_state.world.register::<SpellTemplate>();
+    game_state.world.register::<WantsToCastSpell>();
+    game_state.world.register::<TeachesSpell>();
+    game_state.world.register::<Experience>();
+    game_state.world.register::<ExperienceValue>();
     game_state.world.register::<WantsToMelee>();
     game_state.world.register::<SuffersDamage>();
+    game_state.world.register::<Resistances>();
     game_state.world.register::<Item>();
+    game_state.world.register::<Overburdened>();
     game_state.world.register::<ProvidesHealing>();
     game_state.world.register::<InBackpack>();
     game_state.world.register::<WantsToPickUpItem>();
     game_state.world.register::<WantsToDropItem>();
+    game_state.world.register::<WantsToThrowItem>();
+    game_state.world.register::<Throwable>();
     game_state.world.register::<WantsToUseItem>();
     game_state.world.register::<Consumable>();
+    game_state.world.register::<Stackable>();
     game_state.world.register::<Ranged>();
     game_state.world.register::<InflictsDamage>();
     game_state.world.register::<AreaOfEffect>();
-    game_state.world.register::<Confusion>();
+    game_state.world.register::<InflictsStatus>();
+    game_state.world.register::<StatusEffects>();
     game_state.world.register::<Equippable>();
     game_state.world.register::<Equipped>();
-    game_state.world.register::<MeleePowerBonus>();
+    game_state.world.register::<TwoHanded>();
+    game_state.world.register::<MeleeWeapon>();
+    game_state.world.register::<NaturalAttack>();
     game_state.world.register::<DefenseBonus>();
     game_state.world.register::<WantsToRemoveItem>();
     game_state.world.register::<ParticleLifetime>();
     game_state.world.register::<HungerClock>();
+    game_state.world.register::<LevelTimer>();
     game_state.world.register::<ProvidesFood>();
     game_state.world.register::<MagicMapper>();
+    game_state.world.register::<Teleports>();
+    game_state.world.register::<Identifies>();
+    game_state.world.register::<Recharges>();
+    game_state.world.register::<Charges>();
+    game_state.world.register::<RemovesCurse>();
+    game_state.world.register::<MagicItem>();
+    game_state.world.register::<Cursed>();
     game_state.world.register::<Hidden>();
     game_state.world.register::<EntryTrigger>();
     game_state.world.register::<EntityMoved>();
     game_state.world.register::<SingleActivation>();
+    game_state.world.register::<Alarm>();
+    game_state.world.register::<TrapDoor>();
+    game_state.world.register::<Alerted>();
     game_state.world.register::<SimpleMarker<SerializeMe>>();
     game_state.world.register::<SerializationHelper>();
 
@@ -99,6 +153,16 @@ fn main() -> rltk::BError {
         .insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
     game_state.world.insert(particles::ParticlesBuilder::new());
+    game_state.world.insert(effects::EffectsQueue::new());
+    game_state.world.insert(noise::NoiseQueue::new());
+    game_state.world.insert(status_effects::RoundState::new());
+    game_state
+        .world
+        .insert(character_creation::CharacterBuilder::new());
+    game_state
+        .world
+        .insert(identification::ItemNameTable::new());
+    game_state.world.insert(bosses::SlainUniques::new());
 
     game_state.world.insert(Map::new(1));
     game_state.world.insert(Point::new(0, 0));
@@ -106,6 +170,7 @@ fn main() -> rltk::BError {
 
     let player = spawner::player(&mut game_state.world, 0, 0);
     game_state.world.insert(player);
+    spawner::spells(&mut game_state.world);
 
     game_state.world.insert(GameLog {
         entries: vec!["Welcome to Rusty Roguelike".to_string()],
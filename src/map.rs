@@ -1,14 +1,17 @@
-use std::{collections::HashSet, usize};
+use std::collections::{HashMap, HashSet};
 
 use rltk::{Algorithm2D, BaseMap, Point, Rltk, RGB};
 use serde::{Deserialize, Serialize};
 use specs::Entity;
 
+use crate::memory::Remembered;
+
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
 }
 
 pub type MapTiles = Vec<TileType>;
@@ -25,15 +28,23 @@ pub struct Map {
     pub blocked: TilesBlocking,
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
+    pub dark: bool,
+    pub remembered: HashMap<usize, Remembered>,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     pub tile_content: Vec<Vec<Entity>>,
+
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub light: Vec<RGB>,
 }
 
 pub const MAP_WIDTH: usize = 80;
 pub const MAP_HEIGHT: usize = 43;
 pub const MAP_SIZE: usize = MAP_WIDTH * MAP_HEIGHT;
+/// Tiles in view never render darker than this, so unlit rooms still show their outline.
+const MIN_BRIGHTNESS: f32 = 0.15;
 
 impl Map {
     pub fn new(depth: i32) -> Map {
@@ -44,9 +55,12 @@ impl Map {
             revealed_tiles: vec![false; MAP_SIZE],
             visible_tiles: vec![false; MAP_SIZE],
             blocked: vec![false; MAP_SIZE],
-            depth: depth,
+            depth,
             bloodstains: HashSet::new(),
+            dark: false,
+            remembered: HashMap::new(),
             tile_content: vec![Vec::new(); MAP_SIZE],
+            light: vec![RGB::from_f32(1., 1., 1.); MAP_SIZE],
         }
     }
 
@@ -83,7 +97,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        self.tiles[idx] == TileType::Wall
     }
 
     fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
@@ -144,20 +158,36 @@ pub fn draw_map(map: &Map, ctx: &mut Rltk) {
                         fg = RGB::from_f32(0.5, 0.5, 0.5);
                     }
                     TileType::Wall => {
-                        glyph = wall_glyph(&*map, x, y);
+                        glyph = wall_glyph(map, x, y);
                         fg = RGB::from_f32(0.0, 0.5, 0.0);
                     }
                     TileType::DownStairs => {
                         glyph = rltk::to_cp437('>');
                         fg = RGB::from_f32(0., 1.0, 1.0);
                     }
+                    TileType::UpStairs => {
+                        glyph = rltk::to_cp437('<');
+                        fg = RGB::from_f32(0., 1.0, 1.0);
+                    }
                 }
 
                 let mut bg = RGB::from_f32(0., 0., 0.);
                 if map.bloodstains.contains(&idx) {
                     bg = RGB::from_f32(0.75, 0., 0.);
                 }
-                if !map.visible_tiles[idx] {
+                if map.visible_tiles[idx] {
+                    let light = map
+                        .light
+                        .get(idx)
+                        .copied()
+                        .unwrap_or(RGB::from_f32(1., 1., 1.));
+                    fg = RGB::from_f32(
+                        fg.r * f32::max(MIN_BRIGHTNESS, light.r),
+                        fg.g * f32::max(MIN_BRIGHTNESS, light.g),
+                        fg.b * f32::max(MIN_BRIGHTNESS, light.b),
+                    );
+                    bg = bg * light;
+                } else {
                     fg = fg.to_greyscale();
                     bg = RGB::from_f32(0., 0., 0.);
                 }
@@ -168,7 +198,7 @@ pub fn draw_map(map: &Map, ctx: &mut Rltk) {
 }
 
 fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
-    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 as i32 {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2_i32 {
         return 35;
     }
 
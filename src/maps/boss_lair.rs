@@ -0,0 +1,133 @@
+use rltk::RandomNumberGenerator;
+
+use crate::{
+    bosses,
+    components::Position,
+    map::{Map, TileType},
+    rect::Rect,
+    spawner, SHOW_MAPGEN_VISUALIZER,
+};
+
+use super::{
+    common::{apply_horizontal_tunnel, apply_room_to_map},
+    MapBuilder, SnapshotHistory,
+};
+
+/// Pillars stand on this grid inside the throne hall.
+const PILLAR_SPACING: i32 = 6;
+
+/// A fixed layout for milestone depths: an entry room, a pillared hall holding the boss, and a
+/// stair room behind it.
+pub struct BossLairBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    hall: Rect,
+    history: SnapshotHistory,
+}
+
+impl MapBuilder for BossLairBuilder {
+    fn build_map(&mut self) {
+        let mut rng = RandomNumberGenerator::new();
+        let mid_y = self.map.height / 2;
+
+        let entry = Rect::new(3, mid_y - 3, 8, 6);
+        apply_room_to_map(&mut self.map, &entry);
+        self.take_snapshot();
+
+        let hall_width = rng.range(32, 42);
+        let hall_height = rng.range(22, 30);
+        self.hall = Rect::new(20, mid_y - hall_height / 2, hall_width, hall_height);
+        apply_room_to_map(&mut self.map, &self.hall);
+        for y in (self.hall.y1 + PILLAR_SPACING / 2..self.hall.y2).step_by(PILLAR_SPACING as usize)
+        {
+            for x in
+                (self.hall.x1 + PILLAR_SPACING / 2..self.hall.x2).step_by(PILLAR_SPACING as usize)
+            {
+                if y != mid_y {
+                    let idx = self.map.xy_idx(x, y);
+                    self.map.tiles[idx] = TileType::Wall;
+                }
+            }
+        }
+        self.take_snapshot();
+
+        let stair_room = Rect::new(self.hall.x2 + 6, mid_y - 3, 6, 6);
+        apply_room_to_map(&mut self.map, &stair_room);
+        apply_horizontal_tunnel(&mut self.map, entry.x2, self.hall.x1 + 1, mid_y);
+        apply_horizontal_tunnel(&mut self.map, self.hall.x2, stair_room.x1 + 1, mid_y);
+        self.take_snapshot();
+
+        // The bottom of the dungeon has nowhere further to go.
+        if self.depth < bosses::FINAL_DEPTH {
+            let (stairs_x, stairs_y) = stair_room.center();
+            let stairs_idx = self.map.xy_idx(stairs_x, stairs_y);
+            self.map.tiles[stairs_idx] = TileType::DownStairs;
+        }
+
+        let (start_x, start_y) = entry.center();
+        self.starting_position = Position {
+            x: start_x,
+            y: start_y,
+        };
+    }
+
+    fn spawn_entities(&mut self, world: &mut specs::World) {
+        // The boss holds the far side of the hall, between the player and the stairs.
+        let (center_x, boss_y) = self.hall.center();
+        let boss_x = center_x + (self.hall.x2 - self.hall.x1) / 4;
+        bosses::spawn_boss(world, boss_x, boss_y, self.depth);
+
+        let mut hall_floor = Vec::new();
+        for y in self.hall.y1 + 1..=self.hall.y2 {
+            for x in self.hall.x1 + 1..=self.hall.x2 {
+                let idx = self.map.xy_idx(x, y);
+                if self.map.tiles[idx] == TileType::Floor && (x, y) != (boss_x, boss_y) {
+                    hall_floor.push(idx);
+                }
+            }
+        }
+        let mut budget = spawner::spawn_budget(self.depth) / 2;
+        spawner::spawn_region(
+            world,
+            &hall_floor,
+            self.depth,
+            &spawner::monster_table(self.depth),
+            &mut budget,
+        );
+    }
+
+    fn get_map(&mut self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&mut self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> SnapshotHistory {
+        self.history.clone()
+    }
+
+    fn take_snapshot(&mut self) {
+        if SHOW_MAPGEN_VISUALIZER {
+            let mut snapshot = self.map.clone();
+            for tile in snapshot.revealed_tiles.iter_mut() {
+                *tile = true;
+            }
+            self.history.push(snapshot);
+        }
+    }
+}
+
+impl BossLairBuilder {
+    pub fn new(depth: i32) -> BossLairBuilder {
+        BossLairBuilder {
+            map: Map::new(depth),
+            starting_position: Position { x: 0, y: 0 },
+            depth,
+            hall: Rect::new(0, 0, 0, 0),
+            history: Vec::new(),
+        }
+    }
+}
@@ -4,10 +4,13 @@ use crate::{
     components::Position,
     map::{Map, TileType},
     rect::Rect,
-    spawner, SHOW_MAPGEN_VISUALIZER,
+    SHOW_MAPGEN_VISUALIZER,
 };
 
-use super::{common::apply_room_to_map, MapBuilder, Rooms, SnapshotHistory};
+use super::{
+    common::{apply_room_to_map, spawn_by_region},
+    MapBuilder, Rooms, SnapshotHistory,
+};
 
 pub struct BspDungeonBuilder {
     map: Map,
@@ -43,7 +46,7 @@ impl MapBuilder for BspDungeonBuilder {
             n_rooms += 1;
         }
 
-        self.rooms.sort_by(|a, b| a.x1.cmp(&b.x1));
+        self.rooms.sort_by_key(|a| a.x1);
 
         for i in 0..self.rooms.len() - 1 {
             let room = self.rooms[i];
@@ -70,9 +73,7 @@ impl MapBuilder for BspDungeonBuilder {
     }
 
     fn spawn_entities(&mut self, world: &mut specs::World) {
-        for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(world, room, self.depth);
-        }
+        spawn_by_region(world, &self.map, &self.starting_position, self.depth);
     }
 
     fn get_map(&mut self) -> Map {
@@ -103,7 +104,7 @@ impl BspDungeonBuilder {
         BspDungeonBuilder {
             map: Map::new(depth),
             starting_position: Position { x: 0, y: 0 },
-            depth: depth,
+            depth,
             rooms: Vec::new(),
             history: Vec::new(),
             rects: Vec::new(),
@@ -1,10 +1,8 @@
-use std::usize;
-
 use rltk::RandomNumberGenerator;
 
-use crate::{components::Position, map::Map, rect::Rect, spawner, SHOW_MAPGEN_VISUALIZER};
+use crate::{components::Position, map::Map, rect::Rect, SHOW_MAPGEN_VISUALIZER};
 
-use super::{MapBuilder, Rooms, SnapshotHistory};
+use super::{common::spawn_by_region, MapBuilder, Rooms, SnapshotHistory};
 
 const MIN_ROOM_SIZE: i32 = 8;
 
@@ -68,9 +66,7 @@ impl MapBuilder for BspInteriorBuilder {
     }
 
     fn spawn_entities(&mut self, world: &mut specs::World) {
-        for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(world, room, self.depth);
-        }
+        spawn_by_region(world, &self.map, &self.starting_position, self.depth);
     }
 
     fn get_map(&mut self) -> Map {
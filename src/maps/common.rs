@@ -1,10 +1,21 @@
-use std::cmp::{max, min};
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+};
+
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
+use specs::{World, WorldExt};
 
 use crate::{
+    components::Position,
     map::{Map, TileType},
     rect::Rect,
+    spawner,
 };
 
+/// Nothing spawns this close to where the player arrives, whether from above or below.
+const SAFE_START_RADIUS: f32 = 4.0;
+
 pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
     for y in room.y1 + 1..=room.y2 {
         for x in room.x1 + 1..=room.x2 {
@@ -31,3 +42,61 @@ pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
         }
     }
 }
+
+/// Splits the level's floor into Voronoi noise cells, leaving out the start area and stairs.
+pub fn generate_voronoi_spawn_regions(
+    map: &Map,
+    start: &Position,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<Vec<usize>> {
+    let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
+    let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    noise.set_noise_type(rltk::NoiseType::Cellular);
+    noise.set_frequency(0.08);
+    noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
+
+    // Climbing back up regenerates the level above, so its down stairs are an arrival point too.
+    let mut arrivals = vec![Point::new(start.x, start.y)];
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if *tile == TileType::DownStairs {
+            arrivals.push(Point::new(idx as i32 % map.width, idx as i32 / map.width));
+        }
+    }
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] != TileType::Floor
+                || arrivals.iter().any(|arrival| {
+                    DistanceAlg::Pythagoras.distance2d(*arrival, Point::new(x, y))
+                        < SAFE_START_RADIUS
+                })
+            {
+                continue;
+            }
+            let cell_value = (noise.get_noise(x as f32, y as f32) * 10240.0) as i32;
+            noise_areas.entry(cell_value).or_default().push(idx);
+        }
+    }
+
+    let mut regions: Vec<(i32, Vec<usize>)> = noise_areas.into_iter().collect();
+    regions.sort_by_key(|(cell, _area)| *cell);
+    regions.into_iter().map(|(_cell, area)| area).collect()
+}
+
+/// Spawns a level's monsters and items region by region within its depth's spawn budget.
+pub fn spawn_by_region(world: &mut World, map: &Map, start: &Position, depth: i32) {
+    let mut regions = {
+        let mut rng = world.write_resource::<RandomNumberGenerator>();
+        generate_voronoi_spawn_regions(map, start, &mut rng)
+    };
+    // The shopkeeper claims their tile before anything else is placed.
+    spawner::place_vendor(world, &mut regions, depth);
+    let spawn_table = spawner::room_table(depth);
+    let mut budget = spawner::spawn_budget(depth);
+    for area in regions.iter() {
+        if budget <= 0 {
+            break;
+        }
+        spawner::spawn_region(world, area, depth, &spawn_table, &mut budget);
+    }
+}
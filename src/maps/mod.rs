@@ -1,14 +1,17 @@
 use specs::World;
 
-use crate::{components::Position, map::Map, rect::Rect};
+use crate::{bosses, components::Position, map::Map, rect::Rect};
 
+mod boss_lair;
 mod bsp_dungeon;
 mod bsp_interior;
 mod common;
 mod simple_map;
 
 use self::simple_map::SimpleMapBuilder;
-use self::{bsp_dungeon::BspDungeonBuilder, bsp_interior::BspInteriorBuilder};
+use self::{
+    boss_lair::BossLairBuilder, bsp_dungeon::BspDungeonBuilder, bsp_interior::BspInteriorBuilder,
+};
 
 pub type Rooms = Vec<Rect>;
 pub type SnapshotHistory = Vec<Map>;
@@ -23,6 +26,9 @@ pub trait MapBuilder {
 }
 
 pub fn random_builder(depth: i32) -> Box<dyn MapBuilder> {
+    if bosses::is_boss_level(depth) {
+        return Box::new(BossLairBuilder::new(depth));
+    }
     let mut rng = rltk::RandomNumberGenerator::new();
     match rng.roll_dice(1, 3) {
         1 => Box::new(SimpleMapBuilder::new(depth)),
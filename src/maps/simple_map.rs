@@ -1,13 +1,13 @@
 use super::{
-    common::{apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel},
+    common::{apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, spawn_by_region},
     Rooms, SnapshotHistory,
 };
+use crate::rect::Rect;
 use crate::{
     components::Position,
     map::{Map, TileType},
     SHOW_MAPGEN_VISUALIZER,
 };
-use crate::{rect::Rect, spawner};
 use rltk::RandomNumberGenerator;
 
 use super::MapBuilder;
@@ -26,9 +26,7 @@ impl MapBuilder for SimpleMapBuilder {
     }
 
     fn spawn_entities(&mut self, world: &mut specs::World) {
-        for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(world, room, self.depth);
-        }
+        spawn_by_region(world, &self.map, &self.starting_position, self.depth);
     }
 
     fn get_map(&mut self) -> Map {
@@ -1,29 +1,55 @@
+use rltk::{DiceType, RandomNumberGenerator};
+
 use crate::{
+    attributes,
     components::{
-        CombatStats, DefenseBonus, Equipped, HungerClock, HungerState, MeleePowerBonus, Name,
-        Position, SuffersDamage, WantsToMelee,
+        Alerted, Asleep, Attributes, CombatStats, DamageType, DefenseBonus, Equipped, HungerClock,
+        HungerState, MeleeWeapon, Monster, Name, NaturalAttack, Overburdened, Position,
+        Resistances, Skills, WantsToMelee,
     },
+    damage,
+    effects::{EffectType, EffectsQueue, Targets},
+    encumbrance::OVERBURDENED_PENALTY,
     gamelog::GameLog,
-    particles::ParticlesBuilder,
+    noise::{is_unaware, NoiseQueue, COMBAT_VOLUME},
 };
 use specs::prelude::*;
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AttackRoll {
+    Fumble,
+    Miss,
+    Hit,
+    Critical,
+}
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
-        WriteStorage<'a, SuffersDamage>,
-        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, NaturalAttack>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
         ReadStorage<'a, HungerClock>,
-        WriteExpect<'a, ParticlesBuilder>,
+        ReadStorage<'a, Overburdened>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, EffectsQueue>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Alerted>,
+        ReadStorage<'a, Asleep>,
         ReadStorage<'a, Position>,
+        WriteExpect<'a, NoiseQueue>,
+        ReadStorage<'a, Resistances>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -33,33 +59,57 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut wants_melee,
             names,
             combat_stats,
-            mut inflict_damage,
-            melee_power_bonuses,
+            attributes,
+            skills,
+            melee_weapons,
+            natural_attacks,
             defense_bonuses,
             equipped,
             hunger_clocks,
-            mut particles_builder,
+            overburdened,
+            mut rng,
+            mut effects,
+            player_entity,
+            monsters,
+            alerted,
+            asleep,
             positions,
+            mut noise,
+            resistances,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
-                let mut offensive_bonus = 0;
-                for (_item_entity, power_bonus, equipped_by) in
-                    (&entities, &melee_power_bonuses, &equipped).join()
-                {
+                let mut damage_dice = DiceType::new(1, 4, 0);
+                let mut hit_bonus = 0;
+                let mut damage_type = DamageType::Bludgeoning;
+                if let Some(natural) = natural_attacks.get(entity) {
+                    damage_dice = natural.damage;
+                    hit_bonus = natural.hit_bonus;
+                    damage_type = natural.damage_type;
+                }
+                for (weapon, equipped_by) in (&melee_weapons, &equipped).join() {
                     if equipped_by.owner == entity {
-                        offensive_bonus += power_bonus.power;
+                        damage_dice = weapon.damage;
+                        hit_bonus = weapon.hit_bonus;
+                        damage_type = weapon.damage_type;
                     }
                 }
 
+                let attacker_attributes = attributes.get(entity).cloned().unwrap_or_default();
+                let attacker_skills = skills.get(entity).cloned().unwrap_or_default();
+                let mut to_hit =
+                    attributes::melee_to_hit(&attacker_attributes, &attacker_skills) + hit_bonus;
                 if let Some(hunger_clock) = hunger_clocks.get(entity) {
                     if hunger_clock.state == HungerState::WellFed {
-                        offensive_bonus += 1;
+                        to_hit += 1;
                     }
                 }
+                if overburdened.get(entity).is_some() {
+                    to_hit -= OVERBURDENED_PENALTY;
+                }
 
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 if target_stats.hp > 0 {
@@ -73,33 +123,89 @@ impl<'a> System<'a> for MeleeCombatSystem {
                             defensive_bonus += defense_bonus.defense;
                         }
                     }
+                    let target_attributes = attributes
+                        .get(wants_melee.target)
+                        .cloned()
+                        .unwrap_or_default();
+                    let target_skills = skills.get(wants_melee.target).cloned().unwrap_or_default();
+                    let armor_class = attributes::armor_class(&target_attributes, &target_skills)
+                        + defensive_bonus;
 
+                    let sneak_attack = entity == *player_entity
+                        && is_unaware(wants_melee.target, &monsters, &alerted, &asleep);
+                    let attack = if sneak_attack {
+                        AttackRoll::Critical
+                    } else {
+                        roll_attack(&mut rng, to_hit, armor_class)
+                    };
                     if let Some(pos) = positions.get(wants_melee.target) {
-                        particles_builder.request(
-                            pos.x,
-                            pos.y,
-                            rltk::RGB::named(rltk::ORANGE),
-                            rltk::RGB::named(rltk::BLACK),
-                            rltk::to_cp437('‼'),
-                            200.0,
+                        noise.make(pos.x, pos.y, COMBAT_VOLUME);
+                    }
+                    // Hits flash through the damage effect; every other swing still shows.
+                    if matches!(attack, AttackRoll::Fumble | AttackRoll::Miss) {
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('‼'),
+                                fg: rltk::RGB::named(rltk::ORANGE),
+                                bg: rltk::RGB::named(rltk::BLACK),
+                                lifetime_ms: 200.0,
+                            },
+                            Targets::Single {
+                                target: wants_melee.target,
+                            },
                         );
                     }
-
-                    let damage = i32::max(
-                        0,
-                        (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-                    );
-                    if damage == 0 {
-                        log.entries.push(format!(
-                            "{} is unable to hurt {}",
+                    match attack {
+                        AttackRoll::Fumble => log.entries.push(format!(
+                            "{} fumbles an attack on {}!",
                             &name.name, &target_name.name
-                        ));
-                    } else {
-                        log.entries.push(format!(
-                            "{} hits {}, for {} hp",
-                            &name.name, &target_name.name, damage
-                        ));
-                        SuffersDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        )),
+                        AttackRoll::Miss => log
+                            .entries
+                            .push(format!("{} misses {}.", &name.name, &target_name.name)),
+                        AttackRoll::Hit | AttackRoll::Critical => {
+                            let critical = attack == AttackRoll::Critical;
+                            let damage = roll_damage(
+                                &mut rng,
+                                damage_dice,
+                                attributes::melee_damage_bonus(&attacker_attributes),
+                                critical,
+                            );
+                            let dealt = damage::resolved_damage(
+                                wants_melee.target,
+                                damage,
+                                damage_type,
+                                &resistances,
+                                &equipped,
+                            );
+                            if sneak_attack {
+                                log.entries.push(format!(
+                                    "{} sneak attacks {}, for {} hp!",
+                                    &name.name, &target_name.name, dealt
+                                ));
+                            } else if critical {
+                                log.entries.push(format!(
+                                    "{} critically hits {}, for {} hp!",
+                                    &name.name, &target_name.name, dealt
+                                ));
+                            } else {
+                                log.entries.push(format!(
+                                    "{} hits {}, for {} hp",
+                                    &name.name, &target_name.name, dealt
+                                ));
+                            }
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Damage {
+                                    amount: damage,
+                                    damage_type,
+                                },
+                                Targets::Single {
+                                    target: wants_melee.target,
+                                },
+                            );
+                        }
                     }
                 }
             }
@@ -108,3 +214,114 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+/// A natural 1 always fumbles and a natural 20 always lands as a critical; anything in
+/// between hits when the total meets the defender's armor class.
+pub fn resolve_attack(natural_roll: i32, to_hit: i32, armor_class: i32) -> AttackRoll {
+    match natural_roll {
+        1 => AttackRoll::Fumble,
+        20 => AttackRoll::Critical,
+        _ if natural_roll + to_hit >= armor_class => AttackRoll::Hit,
+        _ => AttackRoll::Miss,
+    }
+}
+
+pub fn roll_attack(rng: &mut RandomNumberGenerator, to_hit: i32, armor_class: i32) -> AttackRoll {
+    resolve_attack(rng.roll_dice(1, 20), to_hit, armor_class)
+}
+
+/// Criticals roll the weapon dice twice; the flat bonuses are only added once.
+pub fn roll_damage(
+    rng: &mut RandomNumberGenerator,
+    dice: DiceType,
+    bonus: i32,
+    critical: bool,
+) -> i32 {
+    let mut damage = rng.roll(dice) + bonus;
+    if critical {
+        damage += rng.roll_dice(dice.n_dice, dice.die_type);
+    }
+    i32::max(1, damage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_one_always_fumbles() {
+        assert_eq!(resolve_attack(1, 100, 10), AttackRoll::Fumble);
+    }
+
+    #[test]
+    fn natural_twenty_always_crits() {
+        assert_eq!(resolve_attack(20, -100, 30), AttackRoll::Critical);
+    }
+
+    #[test]
+    fn hits_when_total_meets_armor_class() {
+        assert_eq!(resolve_attack(10, 2, 12), AttackRoll::Hit);
+        assert_eq!(resolve_attack(9, 2, 12), AttackRoll::Miss);
+    }
+
+    #[test]
+    fn natural_twenty_crits_even_when_the_total_would_hit() {
+        assert_eq!(resolve_attack(20, 5, 10), AttackRoll::Critical);
+    }
+
+    #[test]
+    fn natural_one_fumbles_even_when_the_total_would_miss() {
+        assert_eq!(resolve_attack(1, -5, 10), AttackRoll::Fumble);
+    }
+
+    #[test]
+    fn penalties_can_push_a_hit_into_a_miss() {
+        assert_eq!(resolve_attack(15, -3, 12), AttackRoll::Hit);
+        assert_eq!(resolve_attack(14, -3, 12), AttackRoll::Miss);
+    }
+
+    #[test]
+    fn near_natural_rolls_follow_the_armor_class() {
+        assert_eq!(resolve_attack(2, 0, 3), AttackRoll::Miss);
+        assert_eq!(resolve_attack(19, 0, 20), AttackRoll::Miss);
+        assert_eq!(resolve_attack(19, 1, 20), AttackRoll::Hit);
+    }
+
+    #[test]
+    fn damage_stays_within_dice_range() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let dice = rltk::parse_dice_string("1d8+1").unwrap();
+        for _ in 0..1000 {
+            let damage = roll_damage(&mut rng, dice, 2, false);
+            assert!((4..=11).contains(&damage));
+        }
+    }
+
+    #[test]
+    fn criticals_roll_the_dice_twice() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let dice = rltk::parse_dice_string("2d6").unwrap();
+        for _ in 0..1000 {
+            let damage = roll_damage(&mut rng, dice, 3, true);
+            assert!((7..=27).contains(&damage));
+        }
+    }
+
+    #[test]
+    fn criticals_add_the_bonuses_only_once() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let dice = rltk::parse_dice_string("1d1+3").unwrap();
+        assert_eq!(roll_damage(&mut rng, dice, 0, true), 5);
+        assert_eq!(roll_damage(&mut rng, dice, 2, true), 7);
+        assert_eq!(roll_damage(&mut rng, dice, 2, false), 6);
+    }
+
+    #[test]
+    fn damage_is_never_below_one() {
+        let mut rng = RandomNumberGenerator::seeded(3);
+        let dice = rltk::parse_dice_string("1d4-3").unwrap();
+        for _ in 0..100 {
+            assert!(roll_damage(&mut rng, dice, -5, false) >= 1);
+        }
+    }
+}
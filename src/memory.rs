@@ -0,0 +1,127 @@
+use rltk::{FontCharType, Point, Rltk, RGB};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::{
+    components::{Hidden, Monster, Name, ParticleLifetime, Player, Position, Renderable},
+    lighting,
+    map::Map,
+};
+
+/// What the player last saw on a tile that has since gone out of view.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Remembered {
+    pub name: String,
+    pub glyph: FontCharType,
+    pub fg: RGB,
+    pub render_order: i32,
+    pub monster: bool,
+}
+
+/// Refreshes the map's memory of every tile the player can currently make out.
+pub struct MemorySystem {}
+
+impl<'a> System<'a> for MemorySystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Renderable>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, ParticleLifetime>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut map,
+            player_pos,
+            entities,
+            positions,
+            renderables,
+            names,
+            hidden,
+            monsters,
+            players,
+            particles,
+        ) = data;
+
+        let mut seen = vec![false; (map.width * map.height) as usize];
+        for y in 0..map.height {
+            for x in 0..map.width {
+                if lighting::can_see_at(&map, *player_pos, x, y) {
+                    let idx = map.xy_idx(x, y);
+                    seen[idx] = true;
+                    map.remembered.remove(&idx);
+                }
+            }
+        }
+
+        for (entity, pos, render, name, _hidden, _player, _particle) in (
+            &entities,
+            &positions,
+            &renderables,
+            &names,
+            !&hidden,
+            !&players,
+            !&particles,
+        )
+            .join()
+        {
+            let idx = map.xy_idx(pos.x, pos.y);
+            if !seen[idx] {
+                continue;
+            }
+            // Only the topmost thing on a tile is remembered, just as only it is drawn.
+            let covered = map
+                .remembered
+                .get(&idx)
+                .is_some_and(|memory| memory.render_order <= render.render_order);
+            if !covered {
+                map.remembered.insert(
+                    idx,
+                    Remembered {
+                        name: name.name.clone(),
+                        glyph: render.glyph,
+                        fg: render.fg,
+                        render_order: render.render_order,
+                        monster: monsters.get(entity).is_some(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Draws remembered things on tiles out of sight: objects dimmed, monsters greyed out on a
+/// dark red "last seen" background.
+pub fn draw_memories(map: &Map, player_pos: Point, ctx: &mut Rltk) {
+    for (idx, memory) in map.remembered.iter() {
+        let x = *idx as i32 % map.width;
+        let y = *idx as i32 / map.width;
+        if lighting::can_see_at(map, player_pos, x, y) {
+            continue;
+        }
+        if memory.monster {
+            ctx.set(
+                x,
+                y,
+                memory.fg.to_greyscale(),
+                RGB::from_f32(0.3, 0., 0.),
+                memory.glyph,
+            );
+        } else {
+            ctx.set(
+                x,
+                y,
+                memory.fg * 0.5,
+                RGB::from_f32(0., 0., 0.),
+                memory.glyph,
+            );
+        }
+    }
+}
@@ -1,17 +1,31 @@
-use rltk::Point;
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
 
 use crate::{
-    components::{Confusion, EntityMoved, Monster, Position, Viewshed, WantsToMelee},
+    attributes,
+    components::{
+        Alerted, Asleep, Attributes, Boss, CombatStats, EntityMoved, EntryTrigger, Hidden, Monster,
+        Name, Position, Skills, StatusEffectType, StatusEffects, Viewshed, WantsToMelee,
+    },
+    effects::{EffectType, EffectsQueue, Targets},
+    gamelog::GameLog,
+    lighting,
     map::Map,
-    particles::ParticlesBuilder,
+    status_effects::{self, RoundState},
     RunState,
 };
 
+/// How long a boss stays hasted after it is first driven below half health.
+const ENRAGE_TURNS: i32 = 20;
+/// A d20 roll must reach this, plus the player's stealth, for a monster to spot them.
+const NOTICE_DIFFICULTY: i32 = 6;
+/// Extra difficulty of spotting a player standing in the dark.
+const DARKNESS_PENALTY: i32 = 4;
+
 pub struct MonsterAI {}
 
 impl<'a> System<'a> for MonsterAI {
-    #[allow(clippy::clippy::type_complexity)]
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
         ReadExpect<'a, Point>,
@@ -22,9 +36,21 @@ impl<'a> System<'a> for MonsterAI {
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, StatusEffects>,
         WriteStorage<'a, EntityMoved>,
-        WriteExpect<'a, ParticlesBuilder>,
+        WriteStorage<'a, Boss>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, EffectsQueue>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Alerted>,
+        ReadStorage<'a, EntryTrigger>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, Asleep>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadExpect<'a, RoundState>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -38,68 +64,207 @@ impl<'a> System<'a> for MonsterAI {
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            status_effects,
             mut entity_moved,
-            mut particles_builder,
+            mut bosses,
+            combat_stats,
+            names,
+            mut effects,
+            mut log,
+            mut alerted,
+            traps,
+            hidden,
+            asleep,
+            attributes,
+            skills,
+            mut rng,
+            round,
         ) = data;
 
-        if *run_state != RunState::MonsterTurn {
-            return;
+        let extra_turn = match *run_state {
+            RunState::MonsterTurn => false,
+            // Hasted monsters keep pace with the player's first turn of the round only.
+            RunState::PlayerTurn if round.extra_turn_taken => return,
+            RunState::PlayerTurn => true,
+            _ => return,
+        };
+
+        // Monsters path around traps they can see, but walk straight into hidden ones.
+        let mut avoided_traps = Vec::new();
+        for (_trap, trap_pos, _hidden) in (&traps, &position, !&hidden).join() {
+            let idx = map.xy_idx(trap_pos.x, trap_pos.y);
+            if !map.blocked[idx] {
+                map.blocked[idx] = true;
+                avoided_traps.push(idx);
+            }
+        }
+
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let mut stealth = attributes::stealth_bonus(
+            &attributes.get(*player_entity).cloned().unwrap_or_default(),
+            &skills.get(*player_entity).cloned().unwrap_or_default(),
+        );
+        if !lighting::is_lit(&map, player_idx) {
+            stealth += DARKNESS_PENALTY;
         }
 
-        for (entity, viewshed, _monster, mut pos) in
+        for (entity, viewshed, _monster, pos) in
             (&entities, &mut viewshed, &monster, &mut position).join()
         {
-            let is_confused = confused.get_mut(entity);
-            match is_confused {
-                Some(monster_confused) => {
-                    monster_confused.turns -= 1;
-                    if monster_confused.turns < 1 {
-                        confused.remove(entity);
-                    }
+            let statuses = status_effects.get(entity);
+            if extra_turn && !status_effects::has_extra_turn(statuses) {
+                continue;
+            }
+            if !status_effects::can_act(statuses) || asleep.get(entity).is_some() {
+                continue;
+            }
 
-                    particles_builder.request(
-                        pos.x,
-                        pos.y,
-                        rltk::RGB::named(rltk::MAGENTA),
-                        rltk::RGB::named(rltk::BLACK),
-                        rltk::to_cp437('?'),
-                        200.0,
-                    );
+            if status_effects::has_status(statuses, StatusEffectType::Confusion) {
+                continue;
+            }
+
+            // Seeing the player is not the same as noticing them; each turn in view is a
+            // fresh chance to pick them out.
+            let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+            if alerted.get(entity).is_none()
+                && sees_player
+                && rng.roll_dice(1, 20) >= NOTICE_DIFFICULTY + stealth
+            {
+                alerted
+                    .insert(entity, Alerted::at(player_pos.x, player_pos.y))
+                    .expect("Unable to insert alert");
+            }
+            let mut destination = None;
+            if let Some(alert) = alerted.get_mut(entity) {
+                destination = investigate(
+                    alert,
+                    Point::new(pos.x, pos.y),
+                    sees_player.then_some(*player_pos),
+                );
+                if destination.is_none() {
+                    alerted.remove(entity);
+                }
+            }
+            if let Some(boss) = bosses.get_mut(entity) {
+                // A boss that has been disturbed never lets the player out of its lair.
+                boss.aware |= destination.is_some();
+                if boss.aware {
+                    destination = Some(*player_pos);
                 }
-                None => {
-                    let distance = rltk::DistanceAlg::Pythagoras
-                        .distance2d(Point::new(pos.x, pos.y), *player_pos);
-                    if distance < 1.5 {
-                        wants_to_melee
-                            .insert(
-                                entity,
-                                WantsToMelee {
-                                    target: *player_entity,
-                                },
-                            )
-                            .expect("Unable to insert attack");
-                    } else if viewshed.visible_tiles.contains(&*player_pos) {
-                        let path = rltk::a_star_search(
-                            map.xy_idx(pos.x, pos.y),
-                            map.xy_idx(player_pos.x, player_pos.y),
-                            &mut *map,
-                        );
-                        if path.success && path.steps.len() > 1 {
-                            let mut idx = map.xy_idx(pos.x, pos.y);
-                            map.blocked[idx] = false;
-                            pos.x = path.steps[1] as i32 % map.width;
-                            pos.y = path.steps[1] as i32 / map.width;
-                            entity_moved
-                                .insert(entity, EntityMoved {})
-                                .expect("Unable to insert maker");
-                            idx = map.xy_idx(pos.x, pos.y);
-                            map.blocked[idx] = true;
-                            viewshed.dirty = true;
-                        }
+
+                let badly_hurt = combat_stats
+                    .get(entity)
+                    .is_some_and(|stats| stats.hp * 2 < stats.max_hp);
+                if badly_hurt && !boss.enraged {
+                    boss.enraged = true;
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Status {
+                            effect: StatusEffectType::Haste,
+                            turns: ENRAGE_TURNS,
+                        },
+                        Targets::Single { target: entity },
+                    );
+                    if let Some(name) = names.get(entity) {
+                        log.entries
+                            .push(format!("{} flies into a rage!", name.name));
                     }
                 }
             }
+
+            let distance =
+                rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
+            if distance < 1.5 && destination.is_some() {
+                wants_to_melee
+                    .insert(
+                        entity,
+                        WantsToMelee {
+                            target: *player_entity,
+                        },
+                    )
+                    .expect("Unable to insert attack");
+            } else if let Some(destination) = destination {
+                let path = rltk::a_star_search(
+                    map.xy_idx(pos.x, pos.y),
+                    map.xy_idx(destination.x, destination.y),
+                    &*map,
+                );
+                if path.success && path.steps.len() > 1 {
+                    let mut idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[idx] = false;
+                    pos.x = path.steps[1] as i32 % map.width;
+                    pos.y = path.steps[1] as i32 / map.width;
+                    entity_moved
+                        .insert(entity, EntityMoved {})
+                        .expect("Unable to insert maker");
+                    idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[idx] = true;
+                    viewshed.dirty = true;
+                }
+            }
+        }
+
+        for idx in avoided_traps {
+            map.blocked[idx] = false;
+        }
+    }
+}
+
+/// Where an alerted monster heads this turn: straight for the player while it can see them,
+/// otherwise to the last place it saw or heard something. `None` once it gets there, or runs
+/// out of patience, without finding anyone.
+fn investigate(alert: &mut Alerted, pos: Point, player: Option<Point>) -> Option<Point> {
+    if let Some(player) = player {
+        *alert = Alerted::at(player.x, player.y);
+        return Some(player);
+    }
+    alert.turns -= 1;
+    let spot = Point::new(alert.x, alert.y);
+    if alert.turns < 1 || spot == pos {
+        None
+    } else {
+        Some(spot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise::ALERT_TURNS;
+
+    #[test]
+    fn alerted_monsters_head_for_the_noise() {
+        let mut alert = Alerted::at(10, 10);
+        assert_eq!(
+            investigate(&mut alert, Point::new(2, 2), None),
+            Some(Point::new(10, 10))
+        );
+    }
+
+    #[test]
+    fn alerted_monsters_give_up_where_the_noise_was() {
+        let mut alert = Alerted::at(10, 10);
+        assert_eq!(investigate(&mut alert, Point::new(10, 10), None), None);
+    }
+
+    #[test]
+    fn alerted_monsters_lose_track_of_an_unseen_player() {
+        let mut alert = Alerted::at(10, 10);
+        for _ in 1..ALERT_TURNS {
+            assert!(investigate(&mut alert, Point::new(2, 2), None).is_some());
         }
+        assert_eq!(investigate(&mut alert, Point::new(2, 2), None), None);
+    }
+
+    #[test]
+    fn seeing_the_player_renews_the_chase() {
+        let mut alert = Alerted::at(10, 10);
+        alert.turns = 1;
+        let player = Point::new(4, 3);
+        assert_eq!(
+            investigate(&mut alert, Point::new(2, 2), Some(player)),
+            Some(player)
+        );
+        assert_eq!((alert.x, alert.y, alert.turns), (4, 3, ALERT_TURNS));
     }
 }
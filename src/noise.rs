@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{Alerted, Asleep, Attributes, Boss, Monster, Name, Position, Skills},
+    gamelog::GameLog,
+    map::{Map, TileType},
+};
+
+/// How far (in steps around walls) each kind of noise carries.
+pub const FOOTSTEP_VOLUME: i32 = 3;
+pub const COMBAT_VOLUME: i32 = 8;
+pub const IMPACT_VOLUME: i32 = 6;
+pub const TRAP_VOLUME: i32 = 8;
+/// How many turns an alerted monster searches without seeing the player before giving up.
+pub const ALERT_TURNS: i32 = 20;
+/// One in this many monsters placed with a level is found asleep.
+const SLEEPER_CHANCE: i32 = 3;
+
+struct Noise {
+    x: i32,
+    y: i32,
+    volume: i32,
+}
+
+pub struct NoiseQueue {
+    noises: Vec<Noise>,
+}
+
+impl NoiseQueue {
+    pub fn new() -> NoiseQueue {
+        NoiseQueue { noises: Vec::new() }
+    }
+
+    pub fn make(&mut self, x: i32, y: i32, volume: i32) {
+        if volume > 0 {
+            self.noises.push(Noise { x, y, volume });
+        }
+    }
+}
+
+/// A monster that is asleep, or has not noticed anything yet, cannot defend itself.
+pub fn is_unaware(
+    entity: Entity,
+    monsters: &ReadStorage<Monster>,
+    alerted: &ReadStorage<Alerted>,
+    asleep: &ReadStorage<Asleep>,
+) -> bool {
+    monsters.get(entity).is_some()
+        && (alerted.get(entity).is_none() || asleep.get(entity).is_some())
+}
+
+/// Stealthy characters tread quietly enough that nothing hears them at all.
+pub fn footstep_volume(attributes: &Attributes, skills: &Skills) -> i32 {
+    i32::max(
+        0,
+        FOOTSTEP_VOLUME - attributes::stealth_bonus(attributes, skills),
+    )
+}
+
+/// Walking distance from (x, y) to every tile within `volume` steps; walls stop the sound.
+fn propagate(map: &Map, x: i32, y: i32, volume: i32) -> Vec<Option<i32>> {
+    let mut distances = vec![None; (map.width * map.height) as usize];
+    let start = map.xy_idx(x, y);
+    distances[start] = Some(0);
+    let mut open = VecDeque::from([(x, y)]);
+    while let Some((x, y)) = open.pop_front() {
+        let distance = distances[map.xy_idx(x, y)].unwrap_or(0);
+        if distance >= volume {
+            continue;
+        }
+        for (dx, dy) in [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx >= map.width || ny < 0 || ny >= map.height {
+                continue;
+            }
+            let idx = map.xy_idx(nx, ny);
+            if map.tiles[idx] != TileType::Wall && distances[idx].is_none() {
+                distances[idx] = Some(distance + 1);
+                open.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
+
+/// Lets each queued noise spread through the level. Awake monsters that hear it come to
+/// investigate; sleepers only stir when it is loud enough to carry twice the distance.
+pub struct NoiseSystem {}
+
+impl<'a> System<'a> for NoiseSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, NoiseQueue>,
+        Entities<'a>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Asleep>,
+        WriteStorage<'a, Alerted>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map,
+            mut queue,
+            entities,
+            monsters,
+            positions,
+            names,
+            mut asleep,
+            mut alerted,
+            mut log,
+        ) = data;
+
+        for noise in queue.noises.drain(..) {
+            let heard = propagate(&map, noise.x, noise.y, noise.volume);
+            for (entity, _monster, pos) in (&entities, &monsters, &positions).join() {
+                let distance = match heard[map.xy_idx(pos.x, pos.y)] {
+                    None => continue,
+                    Some(distance) => distance,
+                };
+                if asleep.get(entity).is_some() {
+                    if distance * 2 > noise.volume {
+                        continue;
+                    }
+                    asleep.remove(entity);
+                    if map.visible_tiles[map.xy_idx(pos.x, pos.y)] {
+                        if let Some(name) = names.get(entity) {
+                            log.entries.push(format!("{} wakes up.", name.name));
+                        }
+                    }
+                }
+                alerted
+                    .insert(entity, Alerted::at(noise.x, noise.y))
+                    .expect("Unable to insert alert");
+            }
+        }
+    }
+}
+
+/// Puts some of a freshly generated level's monsters to sleep. Bosses are always waiting.
+pub fn settle_sleepers(world: &mut World) {
+    let entities = world.entities();
+    let monsters = world.read_storage::<Monster>();
+    let bosses = world.read_storage::<Boss>();
+    let mut asleep = world.write_storage::<Asleep>();
+    let mut rng = world.write_resource::<RandomNumberGenerator>();
+    for (entity, _monster, _boss) in (&entities, &monsters, !&bosses).join() {
+        if rng.roll_dice(1, SLEEPER_CHANCE) == 1 {
+            asleep
+                .insert(entity, Asleep {})
+                .expect("Unable to insert sleep");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use rltk::{Rltk, RGB};
 use specs::prelude::*;
 
-use crate::components::{ParticleLifetime, Position, Renderable};
+use crate::components::{LightSource, ParticleLifetime, Position, Renderable};
 
 struct ParticleRequest {
     x: i32,
@@ -12,14 +12,24 @@ struct ParticleRequest {
     lifetime_ms: f32,
 }
 
+struct FlashRequest {
+    x: i32,
+    y: i32,
+    colour: RGB,
+    range: i32,
+    lifetime_ms: f32,
+}
+
 pub struct ParticlesBuilder {
     requests: Vec<ParticleRequest>,
+    flashes: Vec<FlashRequest>,
 }
 
 impl ParticlesBuilder {
     pub fn new() -> ParticlesBuilder {
         ParticlesBuilder {
             requests: Vec::new(),
+            flashes: Vec::new(),
         }
     }
 
@@ -41,6 +51,17 @@ impl ParticlesBuilder {
             lifetime_ms,
         })
     }
+
+    /// A light that burns out as quickly as a particle.
+    pub fn request_flash(&mut self, x: i32, y: i32, colour: RGB, range: i32, lifetime_ms: f32) {
+        self.flashes.push(FlashRequest {
+            x,
+            y,
+            colour,
+            range,
+            lifetime_ms,
+        })
+    }
 }
 
 pub struct ParticleSpawnSystem {}
@@ -52,10 +73,18 @@ impl<'a> System<'a> for ParticleSpawnSystem {
         WriteStorage<'a, Renderable>,
         WriteStorage<'a, ParticleLifetime>,
         WriteExpect<'a, ParticlesBuilder>,
+        WriteStorage<'a, LightSource>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut positions, mut renderables, mut particles, mut particles_builder) = data;
+        let (
+            entities,
+            mut positions,
+            mut renderables,
+            mut particles,
+            mut particles_builder,
+            mut lights,
+        ) = data;
         for particle_req in particles_builder.requests.iter() {
             let particle = entities.create();
             positions
@@ -88,7 +117,38 @@ impl<'a> System<'a> for ParticleSpawnSystem {
                 .expect("Unable to inser lifetime");
         }
 
+        for flash in particles_builder.flashes.iter() {
+            let light = entities.create();
+            positions
+                .insert(
+                    light,
+                    Position {
+                        x: flash.x,
+                        y: flash.y,
+                    },
+                )
+                .expect("Unable to insert position");
+            lights
+                .insert(
+                    light,
+                    LightSource {
+                        colour: flash.colour,
+                        range: flash.range,
+                    },
+                )
+                .expect("Unable to insert light");
+            particles
+                .insert(
+                    light,
+                    ParticleLifetime {
+                        lifetime_ms: flash.lifetime_ms,
+                    },
+                )
+                .expect("Unable to inser lifetime");
+        }
+
         particles_builder.requests.clear();
+        particles_builder.flashes.clear();
     }
 }
 
@@ -98,7 +158,7 @@ pub fn cull_dead_particles(world: &mut World, ctx: &Rltk) {
     {
         let mut particles = world.write_storage::<ParticleLifetime>();
         let entities = world.entities();
-        for (entity, mut particle) in (&entities, &mut particles).join() {
+        for (entity, particle) in (&entities, &mut particles).join() {
             particle.lifetime_ms -= ctx.frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
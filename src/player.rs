@@ -1,16 +1,18 @@
-use std::cmp::{max, min};
-
-use rltk::{Point, Rltk, VirtualKeyCode};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
 use crate::{
+    bosses,
     components::{
-        CombatStats, EntityMoved, HungerClock, HungerState, Item, Monster, Player, Position,
-        Viewshed, WantsToMelee, WantsToPickUpItem,
+        Attributes, CombatStats, EntityMoved, Gold, HungerClock, HungerState, Item, Monster,
+        Player, Position, Skills, StatusEffectType, StatusEffects, Vendor, Viewshed, WantsToMelee,
+        WantsToPickUpItem,
     },
     gamelog::GameLog,
+    gui::{ItemMenuState, VendorMode},
     map::{Map, TileType},
-    RunState,
+    noise::{self, NoiseQueue},
+    spells, status_effects, traps, RunState,
 };
 
 pub fn player_input(world: &mut World, ctx: &mut Rltk) -> RunState {
@@ -18,31 +20,65 @@ pub fn player_input(world: &mut World, ctx: &mut Rltk) -> RunState {
         None => return RunState::AwaitingInput,
         Some(key) => match key {
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, world)
+                return try_move_player(-1, 0, world)
             }
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, world)
+                return try_move_player(1, 0, world)
             }
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, world)
+                return try_move_player(0, -1, world)
             }
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, world)
+                return try_move_player(0, 1, world)
             }
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => try_move_player(1, -1, world),
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => try_move_player(-1, -1, world),
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => try_move_player(1, 1, world),
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => try_move_player(-1, 1, world),
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => return try_move_player(1, -1, world),
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => return try_move_player(-1, -1, world),
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => return try_move_player(1, 1, world),
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => return try_move_player(-1, 1, world),
             VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return skip_turn(world),
-            VirtualKeyCode::D => return RunState::ShowDropItem,
+            VirtualKeyCode::D => {
+                return RunState::ShowDropItem {
+                    menu: ItemMenuState::default(),
+                }
+            }
             VirtualKeyCode::G => get_item(world),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::I => {
+                return RunState::ShowInventory {
+                    menu: ItemMenuState::default(),
+                }
+            }
+            VirtualKeyCode::R => {
+                return RunState::ShowRemoveItem {
+                    menu: ItemMenuState::default(),
+                }
+            }
+            VirtualKeyCode::C => return RunState::ShowEquipment,
+            VirtualKeyCode::T => {
+                return RunState::ShowThrowItem {
+                    menu: ItemMenuState::default(),
+                }
+            }
+            VirtualKeyCode::Key1 => return spells::cast_from_hotbar(world, 0),
+            VirtualKeyCode::Key2 => return spells::cast_from_hotbar(world, 1),
+            VirtualKeyCode::Key3 => return spells::cast_from_hotbar(world, 2),
+            VirtualKeyCode::Key4 => return spells::cast_from_hotbar(world, 3),
+            VirtualKeyCode::Key5 => return spells::cast_from_hotbar(world, 4),
+            VirtualKeyCode::Key6 => return spells::cast_from_hotbar(world, 5),
+            VirtualKeyCode::Key7 => return spells::cast_from_hotbar(world, 6),
+            VirtualKeyCode::Key8 => return spells::cast_from_hotbar(world, 7),
+            VirtualKeyCode::Key9 => return spells::cast_from_hotbar(world, 8),
             VirtualKeyCode::Period => {
                 if try_next_level(world) {
                     return RunState::NextLevel;
                 }
             }
+            VirtualKeyCode::S => return traps::search(world),
+            VirtualKeyCode::X => return traps::disarm(world),
+            VirtualKeyCode::Comma => {
+                if try_previous_level(world) {
+                    return RunState::PreviousLevel;
+                }
+            }
             VirtualKeyCode::Escape => return RunState::SaveGame,
             _ => return RunState::AwaitingInput,
         },
@@ -51,7 +87,19 @@ pub fn player_input(world: &mut World, ctx: &mut Rltk) -> RunState {
     RunState::PlayerTurn
 }
 
-pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) {
+const DIRECTIONS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) -> RunState {
+    let (delta_x, delta_y) = stumble_if_confused(delta_x, delta_y, world);
     let mut positions = world.write_storage::<Position>();
     let mut players = world.write_storage::<Player>();
     let mut viewshed = world.write_storage::<Viewshed>();
@@ -60,6 +108,10 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) {
     let entities = world.entities();
     let mut wants_to_melee = world.write_storage::<WantsToMelee>();
     let mut entity_moved = world.write_storage::<EntityMoved>();
+    let vendors = world.read_storage::<Vendor>();
+    let attributes = world.read_storage::<Attributes>();
+    let skills = world.read_storage::<Skills>();
+    let mut noises = world.write_resource::<NoiseQueue>();
 
     for (entity, _player, pos, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewshed).join()
@@ -67,7 +119,14 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) {
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
         for potential_target in map.tile_content[destination_idx].iter() {
-            if let Some(_) = combat_stats.get(*potential_target) {
+            if vendors.get(*potential_target).is_some() {
+                return RunState::ShowVendor {
+                    vendor: *potential_target,
+                    mode: VendorMode::Buy,
+                    menu: ItemMenuState::default(),
+                };
+            }
+            if combat_stats.get(*potential_target).is_some() {
                 wants_to_melee
                     .insert(
                         entity,
@@ -76,13 +135,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) {
                         },
                     )
                     .expect("Add target failed");
-                return;
+                return RunState::PlayerTurn;
             }
         }
 
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = (pos.x + delta_x).clamp(0, 79);
+            pos.y = (pos.y + delta_y).clamp(0, 49);
 
             let mut player_pos = world.write_resource::<Point>();
             player_pos.x = pos.x;
@@ -91,10 +150,42 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, world: &mut World) {
             entity_moved
                 .insert(entity, EntityMoved {})
                 .expect("Unable to insert maker");
+            noises.make(
+                pos.x,
+                pos.y,
+                noise::footstep_volume(
+                    &attributes.get(entity).cloned().unwrap_or_default(),
+                    &skills.get(entity).cloned().unwrap_or_default(),
+                ),
+            );
 
             viewshed.dirty = true;
         }
     }
+
+    RunState::PlayerTurn
+}
+
+fn stumble_if_confused(delta_x: i32, delta_y: i32, world: &World) -> (i32, i32) {
+    let player_entity = world.fetch::<Entity>();
+    let status_effects = world.read_storage::<StatusEffects>();
+    if !status_effects::has_status(
+        status_effects.get(*player_entity),
+        StatusEffectType::Confusion,
+    ) {
+        return (delta_x, delta_y);
+    }
+
+    let mut rng = world.write_resource::<RandomNumberGenerator>();
+    if rng.roll_dice(1, 2) == 1 {
+        return (delta_x, delta_y);
+    }
+
+    let mut gamelog = world.fetch_mut::<GameLog>();
+    gamelog
+        .entries
+        .push("You stumble around in confusion.".to_string());
+    DIRECTIONS[(rng.roll_dice(1, 8) - 1) as usize]
 }
 
 pub fn try_next_level(world: &mut World) -> bool {
@@ -112,17 +203,44 @@ pub fn try_next_level(world: &mut World) -> bool {
     }
 }
 
+pub fn try_previous_level(world: &mut World) -> bool {
+    let player_pos = *world.fetch::<Point>();
+    let player_entity = *world.fetch::<Entity>();
+    let (on_stairs, depth) = {
+        let map = world.fetch::<Map>();
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        (map.tiles[player_idx] == TileType::UpStairs, map.depth)
+    };
+
+    let mut gamelog = world.fetch_mut::<GameLog>();
+    if !on_stairs {
+        gamelog
+            .entries
+            .push("There is no way up from here.".to_string());
+        false
+    } else if depth == 1 && !bosses::carries_quest_item(world, player_entity) {
+        gamelog
+            .entries
+            .push("You cannot leave the dungeon without the Amulet of Ages.".to_string());
+        false
+    } else {
+        true
+    }
+}
+
 fn get_item(world: &mut World) {
     let player_pos = world.fetch::<Point>();
     let player_entity = world.fetch::<Entity>();
     let entities = world.entities();
     let items = world.read_storage::<Item>();
+    let gold = world.read_storage::<Gold>();
     let positions = world.read_storage::<Position>();
     let mut gamelog = world.fetch_mut::<GameLog>();
 
     let mut target_item: Option<Entity> = None;
-    for (item_entity, _item, position) in (&entities, &items, &positions).join() {
-        if position.x == player_pos.x && position.y == player_pos.y {
+    for (item_entity, position) in (&entities, &positions).join() {
+        let can_pick_up = items.get(item_entity).is_some() || gold.get(item_entity).is_some();
+        if can_pick_up && position.x == player_pos.x && position.y == player_pos.y {
             target_item = Some(item_entity);
         }
     }
@@ -1,58 +1,245 @@
 use rltk::RandomNumberGenerator;
 
-pub struct RandomEntry {
-    name: String,
+enum Payload<T> {
+    Nothing,
+    Value(T),
+    Table(RandomTable<T>),
+}
+
+struct RandomEntry<T> {
+    payload: Payload<T>,
     weight: i32,
+    min_depth: i32,
+    max_depth: i32,
 }
 
-impl RandomEntry {
-    pub fn new<S: ToString>(name: S, weight: i32) -> RandomEntry {
-        RandomEntry {
-            name: name.to_string(),
-            weight,
-        }
+impl<T> RandomEntry<T> {
+    fn available_at(&self, depth: i32) -> bool {
+        self.weight > 0 && depth >= self.min_depth && depth <= self.max_depth
     }
 }
 
-#[derive(Default)]
-pub struct RandomTable {
-    entries: Vec<RandomEntry>,
-    total_weight: i32,
+/// A weighted table of payloads. Entries can be limited to a range of depths, and an entry can
+/// be a whole sub-table that is rolled in turn ("a scroll", then "which scroll").
+pub struct RandomTable<T> {
+    entries: Vec<RandomEntry<T>>,
+}
+
+impl<T: Clone> Default for RandomTable<T> {
+    fn default() -> Self {
+        RandomTable::new()
+    }
 }
 
-impl RandomTable {
-    pub fn new() -> RandomTable {
+impl<T: Clone> RandomTable<T> {
+    pub fn new() -> RandomTable<T> {
         RandomTable {
             entries: Vec::new(),
-            total_weight: 0,
         }
     }
 
-    pub fn add<S: ToString>(mut self, name: S, weight: i32) -> RandomTable {
-        self.total_weight += weight;
-        self.entries
-            .push(RandomEntry::new(name.to_string(), weight));
+    /// Adds an entry available at every depth. Weights below zero count as zero.
+    pub fn add(self, value: T, weight: i32) -> RandomTable<T> {
+        self.push(Payload::Value(value), weight, i32::MIN, i32::MAX)
+    }
+
+    /// Adds an entry that can only be rolled between `min_depth` and `max_depth` inclusive.
+    pub fn add_between(
+        self,
+        value: T,
+        weight: i32,
+        min_depth: i32,
+        max_depth: i32,
+    ) -> RandomTable<T> {
+        self.push(Payload::Value(value), weight, min_depth, max_depth)
+    }
+
+    /// Adds a weighted chance of rolling nothing at all.
+    pub fn add_nothing(self, weight: i32) -> RandomTable<T> {
+        self.push(Payload::Nothing, weight, i32::MIN, i32::MAX)
+    }
+
+    /// Adds a sub-table; landing on it rolls the sub-table at the same depth.
+    pub fn add_table(self, table: RandomTable<T>, weight: i32) -> RandomTable<T> {
+        self.push(Payload::Table(table), weight, i32::MIN, i32::MAX)
+    }
+
+    /// Adds a sub-table weighted by its own total at `depth`, so it rolls exactly as if its
+    /// entries had been listed inline.
+    pub fn add_inline_table(self, table: RandomTable<T>, depth: i32) -> RandomTable<T> {
+        let weight = table.total_weight(depth);
+        self.add_table(table, weight)
+    }
+
+    fn push(
+        mut self,
+        payload: Payload<T>,
+        weight: i32,
+        min_depth: i32,
+        max_depth: i32,
+    ) -> RandomTable<T> {
+        self.entries.push(RandomEntry {
+            payload,
+            weight: i32::max(0, weight),
+            min_depth,
+            max_depth,
+        });
         self
     }
 
-    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
-        let none = "None".to_string();
+    pub fn total_weight(&self, depth: i32) -> i32 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.available_at(depth))
+            .map(|entry| entry.weight)
+            .sum()
+    }
 
-        if self.total_weight == 0 {
-            return none;
+    /// Rolls the table for `depth`. `None` means a "nothing" entry or no eligible entries.
+    pub fn roll(&self, rng: &mut RandomNumberGenerator, depth: i32) -> Option<T> {
+        let total_weight = self.total_weight(depth);
+        if total_weight == 0 {
+            return None;
         }
-        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
-        let mut index: usize = 0;
 
-        while roll > 0 {
-            if roll < self.entries[index].weight {
-                return self.entries[index].name.clone();
+        let mut roll = rng.roll_dice(1, total_weight) - 1;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.available_at(depth))
+        {
+            if roll < entry.weight {
+                return match &entry.payload {
+                    Payload::Nothing => None,
+                    Payload::Value(value) => Some(value.clone()),
+                    Payload::Table(table) => table.roll(rng, depth),
+                };
             }
+            roll -= entry.weight;
+        }
+
+        None
+    }
+}
 
-            roll -= self.entries[index].weight;
-            index += 1;
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const ROLLS: i32 = 20_000;
+
+    fn frequencies(table: &RandomTable<&'static str>, depth: i32) -> HashMap<&'static str, f64> {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let mut counts: HashMap<&'static str, i32> = HashMap::new();
+        for _i in 0..ROLLS {
+            let result = table.roll(&mut rng, depth).unwrap_or("nothing");
+            *counts.entry(result).or_insert(0) += 1;
+        }
+        counts
+            .into_iter()
+            .map(|(name, count)| (name, count as f64 / ROLLS as f64))
+            .collect()
+    }
+
+    fn assert_close(frequencies: &HashMap<&'static str, f64>, name: &str, expected: f64) {
+        let actual = frequencies.get(name).copied().unwrap_or(0.0);
+        assert!(
+            (actual - expected).abs() < 0.02,
+            "{} rolled {:.3}, expected {:.3}",
+            name,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn first_entry_can_be_rolled() {
+        let table = RandomTable::new().add("first", 1).add("second", 1);
+        let frequencies = frequencies(&table, 1);
+        assert_close(&frequencies, "first", 0.5);
+        assert_close(&frequencies, "second", 0.5);
+        assert_close(&frequencies, "nothing", 0.0);
+    }
+
+    #[test]
+    fn rolls_follow_weights() {
+        let table = RandomTable::new()
+            .add("common", 6)
+            .add("uncommon", 3)
+            .add("rare", 1);
+        let frequencies = frequencies(&table, 1);
+        assert_close(&frequencies, "common", 0.6);
+        assert_close(&frequencies, "uncommon", 0.3);
+        assert_close(&frequencies, "rare", 0.1);
+    }
+
+    #[test]
+    fn negative_weights_are_clamped() {
+        let table = RandomTable::new().add("cursed", -5).add("fine", 1);
+        assert_eq!(table.total_weight(1), 1);
+        let frequencies = frequencies(&table, 1);
+        assert_close(&frequencies, "fine", 1.0);
+    }
+
+    #[test]
+    fn depth_ranges_limit_entries() {
+        let table = RandomTable::new()
+            .add("anywhere", 1)
+            .add_between("deep", 1, 3, 5);
+        assert_close(&frequencies(&table, 1), "deep", 0.0);
+        assert_close(&frequencies(&table, 3), "deep", 0.5);
+        assert_close(&frequencies(&table, 6), "deep", 0.0);
+    }
+
+    #[test]
+    fn nothing_has_its_own_weight() {
+        let table = RandomTable::new().add_nothing(3).add("loot", 1);
+        let frequencies = frequencies(&table, 1);
+        assert_close(&frequencies, "nothing", 0.75);
+        assert_close(&frequencies, "loot", 0.25);
+    }
+
+    #[test]
+    fn sub_tables_are_rolled_in_turn() {
+        let scrolls = RandomTable::new().add("fireball", 1).add("teleport", 3);
+        let table = RandomTable::new().add("potion", 1).add_table(scrolls, 1);
+        let frequencies = frequencies(&table, 1);
+        assert_close(&frequencies, "potion", 0.5);
+        assert_close(&frequencies, "fireball", 0.125);
+        assert_close(&frequencies, "teleport", 0.375);
+    }
+
+    #[test]
+    fn inline_tables_match_a_flat_table() {
+        for depth in [1, 4] {
+            let flat = RandomTable::new()
+                .add("potion", 4)
+                .add("fireball", 1 + depth)
+                .add_between("mapping", 2, 3, i32::MAX);
+            let scrolls = RandomTable::new().add("fireball", 1 + depth).add_between(
+                "mapping",
+                2,
+                3,
+                i32::MAX,
+            );
+            let nested = RandomTable::new()
+                .add("potion", 4)
+                .add_inline_table(scrolls, depth);
+            assert_eq!(nested.total_weight(depth), flat.total_weight(depth));
+            let expected = frequencies(&flat, depth);
+            let actual = frequencies(&nested, depth);
+            for name in ["potion", "fireball", "mapping"] {
+                assert_close(&actual, name, expected.get(name).copied().unwrap_or(0.0));
+            }
         }
+    }
 
-        none
+    #[test]
+    fn empty_tables_roll_nothing() {
+        let table: RandomTable<&'static str> = RandomTable::new().add("never", 0);
+        let mut rng = RandomNumberGenerator::seeded(1);
+        assert_eq!(table.roll(&mut rng, 1), None);
     }
 }
@@ -10,7 +10,9 @@ use specs::{
     saveload::{MarkedBuilder, SimpleMarker},
 };
 
-use crate::{components::*, map::Map, map::MAP_SIZE};
+use crate::{
+    bosses::SlainUniques, components::*, identification::ItemNameTable, map::Map, map::MAP_SIZE,
+};
 
 static SAVE_GAME_FILENAME: &str = "./savegame.json";
 
@@ -45,9 +47,15 @@ macro_rules! deserialize_individually {
 
 pub fn save_game(world: &mut World) {
     let map_copy = world.get_mut::<super::map::Map>().unwrap().clone();
+    let item_names = (*world.fetch::<ItemNameTable>()).clone();
+    let slain_uniques = (*world.fetch::<SlainUniques>()).clone();
     let save_helper = world
         .create_entity()
-        .with(SerializationHelper { map: map_copy })
+        .with(SerializationHelper {
+            map: map_copy,
+            item_names,
+            slain_uniques,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -70,33 +78,68 @@ pub fn save_game(world: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SuffersDamage,
+            Attributes,
+            Skills,
+            Mana,
+            Wallet,
+            Gold,
+            Vendor,
+            LootTable,
+            LightSource,
+            Asleep,
+            Boss,
+            QuestItem,
+            KnownSpells,
+            SpellTemplate,
+            WantsToCastSpell,
+            TeachesSpell,
+            Experience,
+            ExperienceValue,
+            Resistances,
             WantsToMelee,
             Item,
+            Overburdened,
             Consumable,
+            Stackable,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            InflictsStatus,
+            StatusEffects,
             ProvidesHealing,
             InBackpack,
             WantsToPickUpItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
+            Throwable,
             SerializationHelper,
             Equippable,
             Equipped,
-            MeleePowerBonus,
+            TwoHanded,
+            MeleeWeapon,
+            NaturalAttack,
             DefenseBonus,
             WantsToRemoveItem,
             ParticleLifetime,
             HungerClock,
+            LevelTimer,
             ProvidesFood,
             MagicMapper,
+            Teleports,
+            Identifies,
+            Recharges,
+            Charges,
+            RemovesCurse,
+            MagicItem,
+            Cursed,
             Hidden,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            Alarm,
+            TrapDoor,
+            Alerted
         );
     }
 
@@ -135,33 +178,68 @@ pub fn load_game(world: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SuffersDamage,
+            Attributes,
+            Skills,
+            Mana,
+            Wallet,
+            Gold,
+            Vendor,
+            LootTable,
+            LightSource,
+            Asleep,
+            Boss,
+            QuestItem,
+            KnownSpells,
+            SpellTemplate,
+            WantsToCastSpell,
+            TeachesSpell,
+            Experience,
+            ExperienceValue,
+            Resistances,
             WantsToMelee,
             Item,
+            Overburdened,
             Consumable,
+            Stackable,
             Ranged,
             InflictsDamage,
             AreaOfEffect,
-            Confusion,
+            InflictsStatus,
+            StatusEffects,
             ProvidesHealing,
             InBackpack,
             WantsToPickUpItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
+            Throwable,
             SerializationHelper,
             Equippable,
             Equipped,
-            MeleePowerBonus,
+            TwoHanded,
+            MeleeWeapon,
+            NaturalAttack,
             DefenseBonus,
             WantsToRemoveItem,
             ParticleLifetime,
             HungerClock,
+            LevelTimer,
             ProvidesFood,
             MagicMapper,
+            Teleports,
+            Identifies,
+            Recharges,
+            Charges,
+            RemovesCurse,
+            MagicItem,
+            Cursed,
             Hidden,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            Alarm,
+            TrapDoor,
+            Alerted
         );
     }
 
@@ -175,6 +253,8 @@ pub fn load_game(world: &mut World) {
             let mut map = world.write_resource::<Map>();
             *map = help.map.clone();
             map.tile_content = vec![Vec::new(); MAP_SIZE];
+            *world.write_resource::<ItemNameTable>() = help.item_names.clone();
+            *world.write_resource::<SlainUniques>() = help.slain_uniques.clone();
             helper_entity = Some(entity);
         }
         for (entity, _player, pos) in (&entities, &player, &position).join() {
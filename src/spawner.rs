@@ -1,4 +1,4 @@
-use std::{collections::HashMap, usize};
+use std::collections::HashMap;
 
 use rltk::{to_cp437, FontCharType, RandomNumberGenerator, RGB};
 use specs::{
@@ -7,21 +7,31 @@ use specs::{
 };
 
 use crate::{
+    attributes, bosses,
     components::{
-        AreaOfEffect, BlocksTile, CombatStats, Confusion, Consumable, DefenseBonus, EntryTrigger,
-        EquipmentSlot, Equippable, Hidden, HungerClock, InflictsDamage, Item, MagicMapper,
-        MeleePowerBonus, Monster, Name, Player, Position, ProvidesFood, ProvidesHealing, Ranged,
-        Renderable, SerializeMe, SingleActivation, Viewshed,
+        Alarm, AreaOfEffect, Attributes, BlocksTile, Boss, Charges, CombatStats, Consumable,
+        DamageType, DefenseBonus, EntryTrigger, EquipmentSlot, Equippable, Equipped, Experience,
+        ExperienceValue, Gold, Hidden, HungerClock, Identifies, InBackpack, InflictsDamage,
+        InflictsStatus, Item, ItemCategory, KnownSpells, LevelTimer, LightSource, LootTable,
+        MagicMapper, Mana, MeleeWeapon, Monster, Name, NaturalAttack, Player, Position,
+        ProvidesFood, ProvidesHealing, QuestItem, Ranged, Recharges, RemovesCurse, Renderable,
+        ResistanceLevel, Resistances, SerializeMe, SingleActivation, Skills, SpellTemplate,
+        Stackable, StatusEffectType, TeachesSpell, Teleports, Throwable, TrapDoor, TwoHanded,
+        Vendor, Viewshed, Wallet,
     },
-    map::MAP_WIDTH,
+    identification::ItemNameTable,
+    magic_items,
+    map::{Map, MAP_WIDTH},
     random_table::RandomTable,
-    rect::Rect,
+    vendors,
 };
 
 const MAX_MONSTERS: i32 = 4;
+/// One in this many ordinary levels has a shopkeeper.
+const VENDOR_CHANCE: i32 = 4;
 
 pub fn player(world: &mut World, player_x: i32, player_y: i32) -> Entity {
-    world
+    let player = world
         .create_entity()
         .with(Position {
             x: player_x,
@@ -32,15 +42,29 @@ pub fn player(world: &mut World, player_x: i32, player_y: i32) -> Entity {
             name: "Player".to_string(),
         })
         .with(CombatStats {
-            max_hp: 30,
-            hp: 30,
-            defense: 2,
-            power: 5,
+            max_hp: attributes::player_max_hp(&Attributes::default(), 1),
+            hp: attributes::player_max_hp(&Attributes::default(), 1),
+        })
+        .with(Attributes::default())
+        .with(Skills::default())
+        .with(Experience { level: 1, xp: 0 })
+        .with(Mana {
+            max: attributes::max_mana(&Attributes::default(), &Skills::default(), 1),
+            current: attributes::max_mana(&Attributes::default(), &Skills::default(), 1),
+            regen_timer: 0,
+        })
+        .with(KnownSpells::default())
+        .with(Wallet { gold: 25 })
+        .with(NaturalAttack {
+            damage: rltk::parse_dice_string("1d4").unwrap(),
+            hit_bonus: 0,
+            damage_type: DamageType::Bludgeoning,
         })
         .with(HungerClock {
             state: crate::components::HungerState::WellFed,
             duration: 20,
         })
+        .with(LevelTimer { turns_on_level: 0 })
         .with(Viewshed {
             visible_tiles: Vec::new(),
             range: 8,
@@ -53,18 +77,202 @@ pub fn player(world: &mut World, player_x: i32, player_y: i32) -> Entity {
             render_order: 0,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build()
+        .build();
+    give_item(world, player, "Torch", 1);
+    player
 }
 
-fn orc(world: &mut World, x: i32, y: i32) {
-    monster(world, x, y, to_cp437('o'), "Orc");
+fn orc(world: &mut World, x: i32, y: i32, map_depth: i32) {
+    let orc = monster(
+        world,
+        x,
+        y,
+        to_cp437('o'),
+        "Orc",
+        "1d6",
+        Attributes {
+            might: 14,
+            fitness: 12,
+            quickness: 8,
+            intelligence: 8,
+        },
+        Skills {
+            melee: 2,
+            defense: 1,
+            magic: 0,
+            traps: 0,
+            stealth: 0,
+        },
+        40,
+        vec![(DamageType::Poison, ResistanceLevel::Resistant)],
+    );
+    let carried = RandomTable::new()
+        .add_nothing(6)
+        .add("Longsword", map_depth)
+        .add("Shield", 2)
+        .roll(
+            &mut world.write_resource::<RandomNumberGenerator>(),
+            map_depth,
+        );
+    if let Some(carried) = carried {
+        give_item(world, orc, carried, map_depth);
+    }
+}
+
+fn goblin(world: &mut World, x: i32, y: i32, map_depth: i32) {
+    let goblin = monster(
+        world,
+        x,
+        y,
+        to_cp437('g'),
+        "Goblin",
+        "1d4",
+        Attributes {
+            might: 8,
+            fitness: 8,
+            quickness: 12,
+            intelligence: 6,
+        },
+        Skills {
+            melee: 1,
+            defense: 0,
+            magic: 0,
+            traps: 0,
+            stealth: 0,
+        },
+        20,
+        vec![(DamageType::Fire, ResistanceLevel::Vulnerable)],
+    );
+    let carried = RandomTable::new()
+        .add_nothing(6)
+        .add("Dagger", 3)
+        .add("Health Potion", 1)
+        .roll(
+            &mut world.write_resource::<RandomNumberGenerator>(),
+            map_depth,
+        );
+    if let Some(carried) = carried {
+        give_item(world, goblin, carried, map_depth);
+    }
+}
+
+fn goblin_king(world: &mut World, x: i32, y: i32, map_depth: i32) {
+    let king = monster(
+        world,
+        x,
+        y,
+        to_cp437('G'),
+        bosses::boss_for_depth(map_depth),
+        "1d8",
+        Attributes {
+            might: 14,
+            fitness: 14,
+            quickness: 14,
+            intelligence: 10,
+        },
+        Skills {
+            melee: 4,
+            defense: 2,
+            magic: 0,
+            traps: 0,
+            stealth: 0,
+        },
+        250,
+        vec![(DamageType::Fire, ResistanceLevel::Vulnerable)],
+    );
+    make_boss(world, king, 60);
+    give_item(world, king, "Longsword", map_depth);
 }
 
-fn goblin(world: &mut World, x: i32, y: i32) {
-    monster(world, x, y, to_cp437('g'), "Goblin");
+fn warlord(world: &mut World, x: i32, y: i32, map_depth: i32) {
+    let warlord = monster(
+        world,
+        x,
+        y,
+        to_cp437('O'),
+        bosses::boss_for_depth(map_depth),
+        "1d10",
+        Attributes {
+            might: 18,
+            fitness: 16,
+            quickness: 12,
+            intelligence: 10,
+        },
+        Skills {
+            melee: 6,
+            defense: 4,
+            magic: 0,
+            traps: 0,
+            stealth: 0,
+        },
+        600,
+        vec![(DamageType::Poison, ResistanceLevel::Resistant)],
+    );
+    make_boss(world, warlord, 110);
+    give_item(world, warlord, "Greataxe", map_depth);
+    give_item(world, warlord, "Amulet of Ages", map_depth);
 }
 
-fn monster<S: ToString>(world: &mut World, x: i32, y: i32, glyph: FontCharType, name: S) {
+fn make_boss(world: &mut World, boss: Entity, hp: i32) {
+    world
+        .write_storage::<CombatStats>()
+        .insert(boss, CombatStats { max_hp: hp, hp })
+        .expect("Unable to insert combat stats");
+    world
+        .write_storage::<Boss>()
+        .insert(
+            boss,
+            Boss {
+                aware: false,
+                enraged: false,
+            },
+        )
+        .expect("Unable to insert boss");
+    if let Some(render) = world.write_storage::<Renderable>().get_mut(boss) {
+        render.fg = RGB::named(rltk::MAGENTA);
+    }
+}
+
+/// Drop table rolled in addition to whatever the monster was carrying when it died.
+pub fn loot_table(table: &str, map_depth: i32) -> RandomTable<&'static str> {
+    match table {
+        "Goblin" => RandomTable::new()
+            .add_nothing(10)
+            .add("Gold", 5)
+            .add("Rations", 3)
+            .add("Health Potion", 2),
+        "Grubnak the Goblin King" => RandomTable::new()
+            .add("Ring of Protection", 1)
+            .add("Tower Shield", 1)
+            .add("Book of Fireball", 1),
+        "Morgrath the Warlord" => RandomTable::new()
+            .add("Amulet of Fire Warding", 1)
+            .add("Ring of Protection", 1),
+        "Orc" => RandomTable::new()
+            .add_nothing(8)
+            .add("Gold", 6 + map_depth)
+            .add("Health Potion", 3)
+            .add("Leather Armor", 1)
+            .add_between("Chain Leggings", map_depth - 1, 2, i32::MAX)
+            .add_table(scroll_table(map_depth), 2),
+        _ => RandomTable::new(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn monster<S: ToString>(
+    world: &mut World,
+    x: i32,
+    y: i32,
+    glyph: FontCharType,
+    name: S,
+    attack_dice: &str,
+    attributes: Attributes,
+    skills: Skills,
+    xp: i32,
+    resistances: Vec<(DamageType, ResistanceLevel)>,
+) -> Entity {
+    let max_hp = attributes::player_max_hp(&attributes, 1);
     world
         .create_entity()
         .with(Position { x, y })
@@ -72,26 +280,104 @@ fn monster<S: ToString>(world: &mut World, x: i32, y: i32, glyph: FontCharType,
         .with(Name {
             name: name.to_string(),
         })
-        .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
+        .with(LootTable {
+            table: name.to_string(),
         })
+        .with(CombatStats { max_hp, hp: max_hp })
+        .with(attributes)
+        .with(skills)
+        .with(ExperienceValue { xp })
         .with(Viewshed {
             visible_tiles: Vec::new(),
             range: 8,
             dirty: true,
         })
+        .with(NaturalAttack {
+            damage: rltk::parse_dice_string(attack_dice).unwrap(),
+            hit_bonus: 0,
+            damage_type: DamageType::Slashing,
+        })
         .with(BlocksTile {})
+        .with(Resistances { resistances })
         .with(Renderable {
-            glyph: glyph,
+            glyph,
             fg: RGB::named(rltk::RED),
             bg: RGB::named(rltk::BLACK),
             render_order: 1,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
+}
+
+/// Spawns `name` straight into `owner`'s hands, wearing it when it is equippable.
+fn give_item(world: &mut World, owner: Entity, name: &str, map_depth: i32) {
+    let (x, y) = match world.read_storage::<Position>().get(owner) {
+        None => return,
+        Some(pos) => (pos.x, pos.y),
+    };
+    for item in spawn_held(world, name, x, y, map_depth) {
+        let slot = world
+            .read_storage::<Equippable>()
+            .get(item)
+            .map(|equippable| equippable.slot);
+        match slot {
+            Some(slot) => {
+                world
+                    .write_storage::<Equipped>()
+                    .insert(item, Equipped { owner, slot })
+                    .expect("Unable to equip item");
+            }
+            None => {
+                world
+                    .write_storage::<InBackpack>()
+                    .insert(item, InBackpack { owner })
+                    .expect("Unable to insert backpack entry");
+            }
+        }
+    }
+}
+
+/// Like `spawn_held`, but equipment never rolls a curse.
+fn spawn_stock(world: &mut World, name: &str, x: i32, y: i32, map_depth: i32) -> Vec<Entity> {
+    match spawn_equipment(world, name, x, y) {
+        Some(item) => {
+            magic_items::enchant_uncursed(world, item, map_depth);
+            world.write_storage::<Position>().remove(item);
+            vec![item]
+        }
+        None => spawn_held(world, name, x, y, map_depth),
+    }
+}
+
+/// A fresh copy of the item spawned as `name`, on nobody's floor and in nobody's hands.
+pub fn spawn_copy(world: &mut World, name: &str) -> Option<Entity> {
+    let map_depth = world.fetch::<Map>().depth;
+    spawn_held(world, name, 0, 0, map_depth).into_iter().next()
+}
+
+/// Spawns `name` at (x, y) and lifts any items it produced off the floor.
+fn spawn_held(world: &mut World, name: &str, x: i32, y: i32, map_depth: i32) -> Vec<Entity> {
+    let items_here = |world: &World| -> Vec<Entity> {
+        let entities = world.entities();
+        let items = world.read_storage::<Item>();
+        let positions = world.read_storage::<Position>();
+        (&entities, &items, &positions)
+            .join()
+            .filter(|(_entity, _item, pos)| pos.x == x && pos.y == y)
+            .map(|(entity, ..)| entity)
+            .collect()
+    };
+    let before = items_here(world);
+    spawn_entity(world, name, x, y, map_depth);
+    let spawned: Vec<Entity> = items_here(world)
+        .into_iter()
+        .filter(|item| !before.contains(item))
+        .collect();
+    let mut positions = world.write_storage::<Position>();
+    for item in spawned.iter() {
+        positions.remove(*item);
+    }
+    spawned
 }
 
 fn rations(world: &mut World, x: i32, y: i32) {
@@ -101,9 +387,14 @@ fn rations(world: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Rations".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            category: ItemCategory::Food,
+            weight: 0.5,
+            base_value: 5,
+        })
         .with(ProvidesFood {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(Renderable {
             glyph: rltk::to_cp437('%'),
             fg: RGB::named(rltk::GREEN),
@@ -114,63 +405,333 @@ fn rations(world: &mut World, x: i32, y: i32) {
         .build();
 }
 
-pub fn spawn_room(world: &mut World, room: &Rect, map_depth: i32) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+fn torch(world: &mut World, x: i32, y: i32) {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Torch".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Weapon,
+            weight: 1.0,
+            base_value: 2,
+        })
+        .with(LightSource {
+            colour: RGB::from_f32(1.0, 0.75, 0.4),
+            range: 5,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// Scenery rather than loot: lights up a patch of otherwise dark cave.
+fn glowing_fungus(world: &mut World, x: i32, y: i32) {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Glowing Fungus".to_string(),
+        })
+        .with(LightSource {
+            colour: RGB::from_f32(0.3, 0.9, 0.4),
+            range: 3,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('"'),
+            fg: RGB::named(rltk::LIME_GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// Total number of spawns a whole level may place at this depth, shared across its regions.
+pub fn spawn_budget(map_depth: i32) -> i32 {
+    12 + map_depth * 3
+}
+
+/// Populates one region of floor tiles, drawing its spawns from the level's remaining `budget`.
+pub fn spawn_region(
+    world: &mut World,
+    area: &[usize],
+    map_depth: i32,
+    spawn_table: &RandomTable<&'static str>,
+    budget: &mut i32,
+) {
+    let mut spawn_points: HashMap<usize, &'static str> = HashMap::new();
+    let mut areas: Vec<usize> = area.to_vec();
 
     {
         let mut rng = world.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3;
+        let num_spawns = i32::min(
+            areas.len() as i32,
+            rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
+        );
+        let num_spawns = i32::min(num_spawns, *budget);
 
         for _i in 0..num_spawns {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 20 {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * MAP_WIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                } else {
-                    tries += 1;
-                }
+            let array_index = if areas.len() == 1 {
+                0usize
+            } else {
+                (rng.roll_dice(1, areas.len() as i32) - 1) as usize
+            };
+            let map_idx = areas.remove(array_index);
+            if let Some(name) = spawn_table.roll(&mut rng, map_depth) {
+                spawn_points.insert(map_idx, name);
             }
+            *budget -= 1;
         }
     }
 
-    for (size, name) in spawn_points.iter() {
-        let x = (*size % MAP_WIDTH) as i32;
-        let y = (*size / MAP_WIDTH) as i32;
-
-        match name.as_ref() {
-            "Goblin" => goblin(world, x, y),
-            "Orc" => orc(world, x, y),
-            "Health Potion" => health_potion(world, x, y),
-            "Fireball Scroll" => fireball_scroll(world, x, y),
-            "Confusion Scroll" => confusion_scroll(world, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(world, x, y),
-            "Magic Mapping Scroll" => magic_mapping_scroll(world, x, y),
-            "Dagger" => dagger(world, x, y),
-            "Shield" => shield(world, x, y),
-            "Longsword" => longsword(world, x, y),
-            "Tower Shield" => tower_shield(world, x, y),
-            "Rations" => rations(world, x, y),
-            "Bear Trap" => bear_trap(world, x, y),
-            _ => {}
+    for (idx, name) in spawn_points.iter() {
+        let x = (*idx % MAP_WIDTH) as i32;
+        let y = (*idx / MAP_WIDTH) as i32;
+        spawn_entity(world, name, x, y, map_depth);
+    }
+}
+
+pub fn spawn_entity(world: &mut World, name: &str, x: i32, y: i32, map_depth: i32) {
+    if let Some(item) = spawn_equipment(world, name, x, y) {
+        magic_items::enchant(world, item, map_depth);
+        return;
+    }
+    match name {
+        "Goblin" => goblin(world, x, y, map_depth),
+        "Orc" => orc(world, x, y, map_depth),
+        "Grubnak the Goblin King" => goblin_king(world, x, y, map_depth),
+        "Morgrath the Warlord" => warlord(world, x, y, map_depth),
+        "Amulet of Ages" => amulet_of_ages(world, x, y),
+        "Health Potion" => health_potion(world, x, y),
+        "Confusion Potion" => confusion_potion(world, x, y),
+        "Fireball Scroll" => fireball_scroll(world, x, y),
+        "Confusion Scroll" => confusion_scroll(world, x, y),
+        "Magic Missile Scroll" => magic_missile_scroll(world, x, y),
+        "Magic Mapping Scroll" => magic_mapping_scroll(world, x, y),
+        "Identify Scroll" => identify_scroll(world, x, y),
+        "Remove Curse Scroll" => remove_curse_scroll(world, x, y),
+        "Recharge Scroll" => recharge_scroll(world, x, y),
+        "Wand of Magic Missile" => magic_missile_wand(world, x, y),
+        "Wand of Confusion" => confusion_wand(world, x, y),
+        "Book of Magic Missile" => spellbook(world, x, y, "Magic Missile"),
+        "Book of Fireball" => spellbook(world, x, y, "Fireball"),
+        "Book of Blink" => spellbook(world, x, y, "Blink"),
+        "Book of Confusion" => spellbook(world, x, y, "Confusion"),
+        "Amulet of Fire Warding" => amulet_of_fire_warding(world, x, y),
+        "Rations" => rations(world, x, y),
+        "Torch" => torch(world, x, y),
+        "Glowing Fungus" => glowing_fungus(world, x, y),
+        "Bear Trap" => bear_trap(world, x, y),
+        "Teleport Trap" => teleport_trap(world, x, y),
+        "Alarm Trap" => alarm_trap(world, x, y),
+        "Trap Door" => trap_door(world, x, y),
+        "Poison Dart Trap" => poison_dart_trap(world, x, y),
+        "Confusion Gas Trap" => confusion_gas_trap(world, x, y),
+        "Gold" => gold(world, x, y, map_depth),
+        _ => {}
+    }
+}
+
+/// Weapons and armor, which may roll an enchantment once spawned.
+fn spawn_equipment(world: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+    let item = match name {
+        "Dagger" => dagger(world, x, y),
+        "Shield" => shield(world, x, y),
+        "Longsword" => longsword(world, x, y),
+        "Tower Shield" => tower_shield(world, x, y),
+        "Greataxe" => greataxe(world, x, y),
+        "Leather Cap" => armor(
+            world,
+            x,
+            y,
+            "Leather Cap",
+            EquipmentSlot::Head,
+            1,
+            Item {
+                category: ItemCategory::Armor,
+                weight: 1.0,
+                base_value: 8,
+            },
+        ),
+        "Leather Armor" => armor(
+            world,
+            x,
+            y,
+            "Leather Armor",
+            EquipmentSlot::Torso,
+            1,
+            Item {
+                category: ItemCategory::Armor,
+                weight: 10.0,
+                base_value: 20,
+            },
+        ),
+        "Chain Leggings" => armor(
+            world,
+            x,
+            y,
+            "Chain Leggings",
+            EquipmentSlot::Legs,
+            1,
+            Item {
+                category: ItemCategory::Armor,
+                weight: 12.0,
+                base_value: 35,
+            },
+        ),
+        "Leather Boots" => armor(
+            world,
+            x,
+            y,
+            "Leather Boots",
+            EquipmentSlot::Feet,
+            1,
+            Item {
+                category: ItemCategory::Armor,
+                weight: 2.0,
+                base_value: 8,
+            },
+        ),
+        "Leather Gloves" => armor(
+            world,
+            x,
+            y,
+            "Leather Gloves",
+            EquipmentSlot::Hands,
+            1,
+            Item {
+                category: ItemCategory::Armor,
+                weight: 1.0,
+                base_value: 8,
+            },
+        ),
+        "Ring of Protection" => ring_of_protection(world, x, y),
+        _ => return None,
+    };
+    Some(item)
+}
+
+fn gold(world: &mut World, x: i32, y: i32, map_depth: i32) {
+    let amount = world
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(2, 6)
+        * map_depth;
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Gold".to_string(),
+        })
+        .with(Gold { amount })
+        .with(Renderable {
+            glyph: rltk::to_cp437('$'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// Gives one in this many levels a shopkeeper, placed on a random free tile of one region.
+pub fn place_vendor(world: &mut World, regions: &mut [Vec<usize>], map_depth: i32) {
+    let (name, idx) = {
+        let mut rng = world.write_resource::<RandomNumberGenerator>();
+        if rng.roll_dice(1, VENDOR_CHANCE) != 1 {
+            return;
+        }
+        let names = vendors::vendor_names();
+        let name = match rng.random_slice_entry(&names) {
+            None => return,
+            Some(name) => name.clone(),
+        };
+        let open: Vec<usize> = (0..regions.len())
+            .filter(|region| !regions[*region].is_empty())
+            .collect();
+        let region = match rng.random_slice_entry(&open) {
+            None => return,
+            Some(region) => &mut regions[*region],
+        };
+        let tile = (rng.roll_dice(1, region.len() as i32) - 1) as usize;
+        (name, region.remove(tile))
+    };
+    let x = (idx % MAP_WIDTH) as i32;
+    let y = (idx / MAP_WIDTH) as i32;
+    vendor(world, x, y, &name, map_depth);
+}
+
+/// Spawns a shopkeeper and stocks its backpack from the vendor data file.
+fn vendor(world: &mut World, x: i32, y: i32, name: &str, map_depth: i32) {
+    let data = match vendors::vendor_data(name) {
+        None => return,
+        Some(data) => data,
+    };
+    let vendor = world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: data.name.clone(),
+        })
+        .with(Vendor {
+            categories: data.categories,
+        })
+        .with(BlocksTile {})
+        .with(LightSource {
+            colour: RGB::from_f32(1.0, 0.9, 0.6),
+            range: 4,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('☺'),
+            fg: RGB::named(rltk::LIGHT_GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    for entry in data.stock.iter() {
+        for item in spawn_stock(world, &entry.name, x, y, map_depth) {
+            world
+                .write_storage::<InBackpack>()
+                .insert(item, InBackpack { owner: vendor })
+                .expect("Unable to stock vendor");
+            if let Some(stack) = world.write_storage::<Stackable>().get_mut(item) {
+                stack.quantity = entry.quantity;
+            }
         }
     }
 }
 
+fn register_unidentified(world: &mut World, name: &str, category: ItemCategory) {
+    let mut rng = world.write_resource::<RandomNumberGenerator>();
+    world
+        .write_resource::<ItemNameTable>()
+        .register(&mut rng, name, category);
+}
+
 fn health_potion(world: &mut World, x: i32, y: i32) {
+    let name = "Health Potion";
+    register_unidentified(world, name, ItemCategory::Potion);
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Health Potion".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Potion,
+            weight: 0.5,
+            base_value: 25,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(ProvidesHealing { heal_amount: 8 })
         .with(Renderable {
             glyph: rltk::to_cp437('¡'),
@@ -182,17 +743,57 @@ fn health_potion(world: &mut World, x: i32, y: i32) {
         .build();
 }
 
+fn confusion_potion(world: &mut World, x: i32, y: i32) {
+    let name = "Confusion Potion";
+    register_unidentified(world, name, ItemCategory::Potion);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Potion,
+            weight: 0.5,
+            base_value: 30,
+        })
+        .with(Consumable {})
+        .with(Stackable { quantity: 1 })
+        .with(InflictsStatus {
+            effect: StatusEffectType::Confusion,
+            turns: 4,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::PINK),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 fn fireball_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Fireball Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Fireball Scroll".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 50,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
+        .with(InflictsDamage {
+            damage: 20,
+            damage_type: DamageType::Fire,
+        })
         .with(AreaOfEffect { radius: 3 })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
@@ -204,17 +805,27 @@ fn fireball_scroll(world: &mut World, x: i32, y: i32) {
         .build();
 }
 
-fn confusion_scroll(world: &mut World, x: i32, y: i32) -> () {
+fn confusion_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Confusion Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Confusion Scroll".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 30,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(Ranged { range: 6 })
-        .with(Confusion { turns: 4 })
+        .with(InflictsStatus {
+            effect: StatusEffectType::Confusion,
+            turns: 4,
+        })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
             fg: RGB::named(rltk::PINK),
@@ -226,16 +837,26 @@ fn confusion_scroll(world: &mut World, x: i32, y: i32) -> () {
 }
 
 fn magic_missile_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Magic Missile Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Magic Missile Scroll".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 25,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 8 })
+        .with(InflictsDamage {
+            damage: 8,
+            damage_type: DamageType::Magic,
+        })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
             fg: RGB::named(rltk::CYAN),
@@ -247,14 +868,21 @@ fn magic_missile_scroll(world: &mut World, x: i32, y: i32) {
 }
 
 fn magic_mapping_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Magic Mapping Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Scroll of Magic Mapping".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 50,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(Stackable { quantity: 1 })
         .with(MagicMapper {})
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
@@ -266,18 +894,254 @@ fn magic_mapping_scroll(world: &mut World, x: i32, y: i32) {
         .build();
 }
 
-fn dagger(world: &mut World, x: i32, y: i32) {
+fn identify_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Identify Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 20,
+        })
+        .with(Consumable {})
+        .with(Stackable { quantity: 1 })
+        .with(Identifies {})
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn remove_curse_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Remove Curse Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 40,
+        })
+        .with(Consumable {})
+        .with(Stackable { quantity: 1 })
+        .with(RemovesCurse {})
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn recharge_scroll(world: &mut World, x: i32, y: i32) {
+    let name = "Recharge Scroll";
+    register_unidentified(world, name, ItemCategory::Scroll);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 0.1,
+            base_value: 60,
+        })
+        .with(Consumable {})
+        .with(Stackable { quantity: 1 })
+        .with(Recharges {})
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn wand_charges(world: &mut World) -> Charges {
+    let max = world
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 3)
+        + 2;
+    Charges { current: max, max }
+}
+
+fn magic_missile_wand(world: &mut World, x: i32, y: i32) {
+    let charges = wand_charges(world);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Wand of Magic Missile".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Wand,
+            weight: 0.5,
+            base_value: 75,
+        })
+        .with(charges)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 8,
+            damage_type: DamageType::Magic,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('-'),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn confusion_wand(world: &mut World, x: i32, y: i32) {
+    let charges = wand_charges(world);
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Wand of Confusion".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Wand,
+            weight: 0.5,
+            base_value: 90,
+        })
+        .with(charges)
+        .with(Ranged { range: 6 })
+        .with(InflictsStatus {
+            effect: StatusEffectType::Confusion,
+            turns: 4,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('-'),
+            fg: RGB::named(rltk::PINK),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// Spawns the template entity for every spell, which casting points `WantsToUseItem` at.
+pub fn spells(world: &mut World) {
+    world
+        .create_entity()
+        .with(Name {
+            name: "Magic Missile".to_string(),
+        })
+        .with(SpellTemplate { mana_cost: 2 })
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 6,
+            damage_type: DamageType::Magic,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    world
+        .create_entity()
+        .with(Name {
+            name: "Fireball".to_string(),
+        })
+        .with(SpellTemplate { mana_cost: 5 })
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 15,
+            damage_type: DamageType::Fire,
+        })
+        .with(AreaOfEffect { radius: 2 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    world
+        .create_entity()
+        .with(Name {
+            name: "Blink".to_string(),
+        })
+        .with(SpellTemplate { mana_cost: 3 })
+        .with(Ranged { range: 8 })
+        .with(Teleports {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    world
+        .create_entity()
+        .with(Name {
+            name: "Confusion".to_string(),
+        })
+        .with(SpellTemplate { mana_cost: 3 })
+        .with(Ranged { range: 6 })
+        .with(InflictsStatus {
+            effect: StatusEffectType::Confusion,
+            turns: 4,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn spellbook(world: &mut World, x: i32, y: i32, spell: &str) {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: format!("Book of {}", spell),
+        })
+        .with(Item {
+            category: ItemCategory::Scroll,
+            weight: 2.0,
+            base_value: 80,
+        })
+        .with(Consumable {})
+        .with(TeachesSpell {
+            spell: spell.to_string(),
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('+'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn dagger(world: &mut World, x: i32, y: i32) -> Entity {
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
             name: "Dagger".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            category: ItemCategory::Weapon,
+            weight: 1.0,
+            base_value: 10,
+        })
         .with(Equippable {
             slot: crate::components::EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 2 })
+        .with(MeleeWeapon {
+            damage: rltk::parse_dice_string("1d4").unwrap(),
+            hit_bonus: 1,
+            damage_type: DamageType::Piercing,
+        })
+        .with(Throwable {})
         .with(Renderable {
             glyph: rltk::to_cp437('/'),
             fg: RGB::named(rltk::CYAN),
@@ -285,17 +1149,21 @@ fn dagger(world: &mut World, x: i32, y: i32) {
             render_order: 2,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn shield(world: &mut World, x: i32, y: i32) {
+fn shield(world: &mut World, x: i32, y: i32) -> Entity {
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
             name: "Shield".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            category: ItemCategory::Armor,
+            weight: 6.0,
+            base_value: 15,
+        })
         .with(Equippable {
             slot: crate::components::EquipmentSlot::Shield,
         })
@@ -307,21 +1175,29 @@ fn shield(world: &mut World, x: i32, y: i32) {
             render_order: 2,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn longsword(world: &mut World, x: i32, y: i32) {
+fn longsword(world: &mut World, x: i32, y: i32) -> Entity {
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
             name: "Longsword".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            category: ItemCategory::Weapon,
+            weight: 3.0,
+            base_value: 40,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 4 })
+        .with(MeleeWeapon {
+            damage: rltk::parse_dice_string("1d8+1").unwrap(),
+            hit_bonus: 0,
+            damage_type: DamageType::Slashing,
+        })
         .with(Renderable {
             glyph: rltk::to_cp437('/'),
             fg: RGB::named(rltk::YELLOW),
@@ -329,21 +1205,28 @@ fn longsword(world: &mut World, x: i32, y: i32) {
             render_order: 2,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
-fn tower_shield(world: &mut World, x: i32, y: i32) {
+fn tower_shield(world: &mut World, x: i32, y: i32) -> Entity {
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
             name: "Tower Shield".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            category: ItemCategory::Armor,
+            weight: 15.0,
+            base_value: 60,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Shield,
         })
         .with(DefenseBonus { defense: 3 })
+        .with(Resistances {
+            resistances: vec![(DamageType::Fire, ResistanceLevel::Resistant)],
+        })
         .with(Renderable {
             glyph: rltk::to_cp437('('),
             fg: RGB::named(rltk::YELLOW),
@@ -351,43 +1234,407 @@ fn tower_shield(world: &mut World, x: i32, y: i32) {
             render_order: 2,
         })
         .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn greataxe(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Greataxe".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Weapon,
+            weight: 7.0,
+            base_value: 55,
+        })
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(TwoHanded {})
+        .with(MeleeWeapon {
+            damage: rltk::parse_dice_string("1d12").unwrap(),
+            hit_bonus: -1,
+            damage_type: DamageType::Slashing,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn armor(
+    world: &mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    slot: EquipmentSlot,
+    defense: i32,
+    item: Item,
+) -> Entity {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(item)
+        .with(Equippable { slot })
+        .with(DefenseBonus { defense })
+        .with(Renderable {
+            glyph: rltk::to_cp437('['),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn ring_of_protection(world: &mut World, x: i32, y: i32) -> Entity {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Ring of Protection".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Jewelry,
+            weight: 0.1,
+            base_value: 100,
+        })
+        .with(Equippable {
+            slot: EquipmentSlot::LeftRing,
+        })
+        .with(DefenseBonus { defense: 1 })
+        .with(Renderable {
+            glyph: rltk::to_cp437('='),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+fn amulet_of_fire_warding(world: &mut World, x: i32, y: i32) {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Amulet of Fire Warding".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Jewelry,
+            weight: 0.2,
+            base_value: 120,
+        })
+        .with(Equippable {
+            slot: EquipmentSlot::Amulet,
+        })
+        .with(Resistances {
+            resistances: vec![(DamageType::Fire, ResistanceLevel::Resistant)],
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('"'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn bear_trap(world: &mut World, x: i32, y: i32) {
+fn amulet_of_ages(world: &mut World, x: i32, y: i32) {
+    world
+        .create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Amulet of Ages".to_string(),
+        })
+        .with(Item {
+            category: ItemCategory::Jewelry,
+            weight: 0.2,
+            base_value: 10000,
+        })
+        .with(QuestItem {})
+        .with(Renderable {
+            glyph: rltk::to_cp437('"'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn trap<S: ToString>(world: &mut World, x: i32, y: i32, name: S, fg: RGB) -> EntityBuilder<'_> {
     world
         .create_entity()
         .with(Position { x, y })
         .with(Name {
-            name: "Bear Trap".to_string(),
+            name: name.to_string(),
         })
         .with(Hidden {})
         .with(EntryTrigger {})
-        .with(InflictsDamage { damage: 6 })
-        .with(SingleActivation {})
         .with(Renderable {
             glyph: rltk::to_cp437('^'),
-            fg: RGB::named(rltk::RED),
+            fg,
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
+}
+
+fn bear_trap(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Bear Trap", RGB::named(rltk::RED))
+        .with(InflictsDamage {
+            damage: 6,
+            damage_type: DamageType::Piercing,
+        })
+        .with(SingleActivation {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn teleport_trap(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Teleport Trap", RGB::named(rltk::MAGENTA))
+        .with(Teleports {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn room_table(map_depth: i32) -> RandomTable {
+fn alarm_trap(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Alarm Trap", RGB::named(rltk::YELLOW))
+        .with(Alarm {})
+        .with(SingleActivation {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn trap_door(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Trap Door", RGB::named(rltk::BROWN1))
+        .with(TrapDoor {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn poison_dart_trap(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Poison Dart Trap", RGB::named(rltk::GREEN))
+        .with(InflictsDamage {
+            damage: 3,
+            damage_type: DamageType::Piercing,
+        })
+        .with(InflictsStatus {
+            effect: StatusEffectType::Poison,
+            turns: 5,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn confusion_gas_trap(world: &mut World, x: i32, y: i32) {
+    trap(world, x, y, "Confusion Gas Trap", RGB::named(rltk::PINK))
+        .with(InflictsStatus {
+            effect: StatusEffectType::Confusion,
+            turns: 4,
+        })
+        .with(AreaOfEffect { radius: 2 })
+        .with(SingleActivation {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+pub fn monster_table(map_depth: i32) -> RandomTable<&'static str> {
     RandomTable::new()
         .add("Goblin", 10)
         .add("Orc", 1 + map_depth)
+}
+
+pub fn room_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
+        .add_inline_table(monster_table(map_depth), map_depth)
+        .add_inline_table(potion_table(map_depth), map_depth)
+        .add_inline_table(scroll_table(map_depth), map_depth)
+        .add_inline_table(magic_table(map_depth), map_depth)
+        .add_inline_table(equipment_table(map_depth), map_depth)
+        .add("Rations", 10)
+        .add("Torch", 2)
+        .add_between("Glowing Fungus", 4, 3, i32::MAX)
+        .add("Gold", 6)
+        .add_table(trap_table(map_depth), 3 + map_depth)
+}
+
+fn trap_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
+        .add("Bear Trap", 4)
+        .add("Poison Dart Trap", 2 + map_depth)
+        .add("Alarm Trap", 2)
+        .add_between("Teleport Trap", 2, 2, i32::MAX)
+        .add_between("Confusion Gas Trap", map_depth, 3, i32::MAX)
+        .add_between("Trap Door", 2, 2, bosses::FINAL_DEPTH - 1)
+}
+
+fn potion_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
         .add("Health Potion", 7)
+        .add("Confusion Potion", 2 + map_depth)
+}
+
+fn scroll_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
         .add("Fireball Scroll", 2 + map_depth)
         .add("Confusion Scroll", 2 + map_depth)
         .add("Magic Missile Scroll", 4)
         .add("Magic Mapping Scroll", 2)
+        .add("Identify Scroll", 3)
+        .add("Remove Curse Scroll", 2)
+        .add("Recharge Scroll", 2)
+}
+
+fn magic_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
+        .add("Wand of Magic Missile", 2)
+        .add_between("Wand of Confusion", map_depth - 1, 2, i32::MAX)
+        .add("Book of Magic Missile", 1)
+        .add("Book of Blink", 1)
+        .add_between("Book of Confusion", map_depth - 1, 2, i32::MAX)
+        .add_between("Book of Fireball", map_depth - 1, 2, i32::MAX)
+}
+
+fn equipment_table(map_depth: i32) -> RandomTable<&'static str> {
+    RandomTable::new()
         .add("Dagger", 3)
         .add("Shield", 3)
-        .add("Longsword", map_depth - 1)
-        .add("Tower Shield", map_depth - 1)
-        .add("Rations", 10)
-        .add("Bear Trap", 2)
+        .add_between("Longsword", map_depth - 1, 2, i32::MAX)
+        .add_between("Tower Shield", map_depth - 1, 2, i32::MAX)
+        .add_between("Greataxe", map_depth - 1, 2, i32::MAX)
+        .add("Leather Cap", 2)
+        .add("Leather Armor", 2)
+        .add_between("Chain Leggings", map_depth - 1, 2, i32::MAX)
+        .add("Leather Boots", 2)
+        .add("Leather Gloves", 2)
+        .add_between("Ring of Protection", map_depth - 1, 2, i32::MAX)
+        .add_between("Amulet of Fire Warding", map_depth - 1, 2, i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::saveload::SimpleMarkerAllocator;
+
+    use super::*;
+    use crate::inventory;
+
+    /// Spawn keys of every potion and scroll that starts out unidentified.
+    const UNIDENTIFIED_ITEMS: [&str; 9] = [
+        "Health Potion",
+        "Confusion Potion",
+        "Fireball Scroll",
+        "Confusion Scroll",
+        "Magic Missile Scroll",
+        "Magic Mapping Scroll",
+        "Identify Scroll",
+        "Remove Curse Scroll",
+        "Recharge Scroll",
+    ];
+
+    fn item_world() -> World {
+        let mut world = World::new();
+        world.register::<Position>();
+        world.register::<Name>();
+        world.register::<Item>();
+        world.register::<Consumable>();
+        world.register::<Stackable>();
+        world.register::<Renderable>();
+        world.register::<ProvidesHealing>();
+        world.register::<Ranged>();
+        world.register::<InflictsDamage>();
+        world.register::<AreaOfEffect>();
+        world.register::<InflictsStatus>();
+        world.register::<MagicMapper>();
+        world.register::<Identifies>();
+        world.register::<RemovesCurse>();
+        world.register::<Recharges>();
+        world.register::<ProvidesFood>();
+        world.register::<SimpleMarker<SerializeMe>>();
+        world.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        world.insert(RandomNumberGenerator::seeded(1));
+        world.insert(ItemNameTable::new());
+        world.insert(Map::new(1));
+        world
+    }
+
+    #[test]
+    fn unidentified_items_are_registered_under_their_own_name() {
+        let mut world = item_world();
+        for key in UNIDENTIFIED_ITEMS {
+            let spawned = spawn_held(&mut world, key, 1, 1, 1);
+            assert_eq!(spawned.len(), 1, "{} did not spawn one item", key);
+            let names = world.read_storage::<Name>();
+            let name = &names.get(spawned[0]).unwrap().name;
+            assert_eq!(name, key);
+            assert!(
+                !world.fetch::<ItemNameTable>().is_identified(name),
+                "{} was never registered as unidentified",
+                name
+            );
+        }
+    }
+
+    macro_rules! assert_same_components {
+        ($world:expr, $original:expr, $copy:expr, $( $type:ty ),*) => {
+            $(
+            assert_eq!(
+                $world.read_storage::<$type>().get($original).is_some(),
+                $world.read_storage::<$type>().get($copy).is_some(),
+                "split_stack copy differs in {}",
+                stringify!($type)
+            );
+            )*
+        };
+    }
+
+    #[test]
+    fn split_stacks_match_the_original_item() {
+        let mut world = item_world();
+        for key in UNIDENTIFIED_ITEMS.iter().chain(["Rations"].iter()) {
+            let stack = spawn_held(&mut world, key, 1, 1, 1)[0];
+            world
+                .write_storage::<Stackable>()
+                .get_mut(stack)
+                .unwrap()
+                .quantity = 3;
+
+            let single = inventory::split_stack(&mut world, stack);
+            assert_ne!(single, stack);
+            let stackables = world.read_storage::<Stackable>();
+            assert_eq!(stackables.get(stack).unwrap().quantity, 3);
+            assert_eq!(stackables.get(single).unwrap().quantity, 1);
+            assert_same_components!(
+                world,
+                stack,
+                single,
+                Name,
+                Item,
+                Consumable,
+                Renderable,
+                ProvidesHealing,
+                Ranged,
+                InflictsDamage,
+                AreaOfEffect,
+                InflictsStatus,
+                MagicMapper,
+                Identifies,
+                RemovesCurse,
+                Recharges,
+                ProvidesFood,
+                SimpleMarker<SerializeMe>
+            );
+            assert_eq!(world.read_storage::<Name>().get(single).unwrap().name, *key);
+        }
+    }
 }
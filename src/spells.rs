@@ -0,0 +1,170 @@
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{
+        Attributes, KnownSpells, Mana, Name, Ranged, Skills, SpellTemplate, WantsToCastSpell,
+        WantsToUseItem,
+    },
+    gamelog::GameLog,
+    state::RunState,
+};
+
+pub fn find_spell(world: &World, name: &str) -> Option<Entity> {
+    let entities = world.entities();
+    let templates = world.read_storage::<SpellTemplate>();
+    let names = world.read_storage::<Name>();
+    (&entities, &templates, &names)
+        .join()
+        .find(|(_entity, _template, spell_name)| spell_name.name == name)
+        .map(|(entity, _template, _name)| entity)
+}
+
+/// Casts the spell in hotbar `slot`, heading to targeting first when it needs a target.
+pub fn cast_from_hotbar(world: &mut World, slot: usize) -> RunState {
+    let player_entity = *world.fetch::<Entity>();
+    let spell_name = match world
+        .read_storage::<KnownSpells>()
+        .get(player_entity)
+        .and_then(|known| known.spells.get(slot))
+    {
+        None => return RunState::AwaitingInput,
+        Some(name) => name.clone(),
+    };
+    let spell = match find_spell(world, &spell_name) {
+        None => return RunState::AwaitingInput,
+        Some(spell) => spell,
+    };
+
+    let mana_cost = world
+        .read_storage::<SpellTemplate>()
+        .get(spell)
+        .unwrap()
+        .mana_cost;
+    let current = world
+        .read_storage::<Mana>()
+        .get(player_entity)
+        .map_or(0, |mana| mana.current);
+    if current < mana_cost {
+        world.write_resource::<GameLog>().entries.push(format!(
+            "You don't have enough mana to cast {}.",
+            spell_name
+        ));
+        return RunState::AwaitingInput;
+    }
+
+    if let Some(ranged) = world.read_storage::<Ranged>().get(spell) {
+        return RunState::ShowTargeting {
+            range: ranged.range,
+            item: spell,
+        };
+    }
+    world
+        .write_storage::<WantsToCastSpell>()
+        .insert(
+            player_entity,
+            WantsToCastSpell {
+                spell,
+                target: None,
+            },
+        )
+        .expect("Unable to insert intent");
+    RunState::PlayerTurn
+}
+
+pub struct SpellCastSystem {}
+
+impl<'a> System<'a> for SpellCastSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToCastSpell>,
+        WriteStorage<'a, WantsToUseItem>,
+        WriteStorage<'a, Mana>,
+        ReadStorage<'a, SpellTemplate>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_cast,
+            mut wants_use,
+            mut mana,
+            templates,
+            names,
+        ) = data;
+
+        for (entity, cast) in (&entities, &wants_cast).join() {
+            let template = templates.get(cast.spell).unwrap();
+            let caster_mana = match mana.get_mut(entity) {
+                Some(caster_mana) if caster_mana.current >= template.mana_cost => caster_mana,
+                _ => continue,
+            };
+            caster_mana.current -= template.mana_cost;
+
+            if entity == *player_entity {
+                gamelog
+                    .entries
+                    .push(format!("You cast {}.", names.get(cast.spell).unwrap().name));
+            }
+            wants_use
+                .insert(
+                    entity,
+                    WantsToUseItem {
+                        item: cast.spell,
+                        target: cast.target,
+                    },
+                )
+                .expect("Unable to insert intent");
+        }
+
+        wants_cast.clear();
+    }
+}
+
+pub struct ManaRegenSystem {}
+
+impl<'a> System<'a> for ManaRegenSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Mana>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, player_entity, runstate, mut mana, attributes, skills) = data;
+
+        for (entity, mana, entity_attributes, entity_skills) in
+            (&entities, &mut mana, &attributes, skills.maybe()).join()
+        {
+            let proceed = match *runstate {
+                RunState::PlayerTurn => entity == *player_entity,
+                RunState::MonsterTurn => entity != *player_entity,
+                _ => false,
+            };
+            if !proceed || mana.current >= mana.max {
+                continue;
+            }
+
+            mana.regen_timer += 1;
+            let default_skills = Skills::default();
+            let interval = attributes::mana_regen_turns(
+                entity_attributes,
+                entity_skills.unwrap_or(&default_skills),
+            );
+            if mana.regen_timer >= interval {
+                mana.regen_timer = 0;
+                mana.current += 1;
+            }
+        }
+    }
+}
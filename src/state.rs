@@ -1,44 +1,91 @@
-use rltk::{GameState, Point, Rltk};
+use rltk::{GameState, Point, RandomNumberGenerator, Rltk};
 use specs::prelude::*;
 use specs::World;
 
 use crate::{
+    bosses::SlainUniques,
+    character_creation::CharacterBuilder,
+    charges,
     components::{
-        CombatStats, Equipped, Hidden, InBackpack, Player, Position, Ranged, Renderable, Viewshed,
-        WantsToDropItem, WantsToRemoveItem, WantsToUseItem,
+        CombatStats, Equipped, Hidden, Identifies, InBackpack, Player, Position, Ranged, Recharges,
+        Renderable, SpellTemplate, StatusEffects, Viewshed, WantsToCastSpell, WantsToDropItem,
+        WantsToRemoveItem, WantsToThrowItem, WantsToUseItem,
     },
     damage::{self, DamageSystem},
+    effects,
+    encumbrance::EncumbranceSystem,
     gamelog::GameLog,
     gui,
     hunger::HungerSystem,
+    identification::{self, ItemNameTable},
     inventory::{ItemColecctionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem},
-    map::{draw_map, Map, MAP_HEIGHT, MAP_WIDTH},
+    leveling,
+    lighting::{self, LightingSystem},
+    map::{draw_map, Map, TileType, MAP_HEIGHT, MAP_WIDTH},
     map_indexing::MapIndexingSystem,
     maps::{self},
     melee_combat::MeleeCombatSystem,
+    memory::{self, MemorySystem},
     monster_ai::MonsterAI,
+    noise::{self, NoiseSystem},
     particles::{self, ParticleSpawnSystem},
     player::player_input,
     saveload, spawner,
+    spells::{ManaRegenSystem, SpellCastSystem},
+    status_effects::{self, RoundState, StatusEffectSystem},
+    throwing::{self, ThrowSystem},
     trigger::TriggerSystem,
+    vendors,
     visibility::VisibilitySystem,
+    wandering::{self, WanderingMonsterSystem},
     SHOW_MAPGEN_VISUALIZER,
 };
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState {
+    CharacterCreation {
+        step: gui::CreationStep,
+        selection: usize,
+    },
     AwaitingInput,
     PreRun,
     PlayerTurn,
     MonsterTurn,
-    ShowInventory,
-    ShowDropItem,
-    ShowRemoveItem,
+    ShowInventory {
+        menu: gui::ItemMenuState,
+    },
+    ShowDropItem {
+        menu: gui::ItemMenuState,
+    },
+    ShowThrowItem {
+        menu: gui::ItemMenuState,
+    },
+    ShowVendor {
+        vendor: Entity,
+        mode: gui::VendorMode,
+        menu: gui::ItemMenuState,
+    },
+    ShowThrowTarget {
+        item: Entity,
+    },
+    ShowRemoveItem {
+        menu: gui::ItemMenuState,
+    },
+    ShowIdentify {
+        scroll: Entity,
+        menu: gui::ItemMenuState,
+    },
+    ShowRecharge {
+        scroll: Entity,
+        menu: gui::ItemMenuState,
+    },
+    ShowEquipment,
     ShowTargeting {
         range: i32,
         item: Entity,
     },
     NextLevel,
+    PreviousLevel,
     MagicMapReveal {
         row: i32,
     },
@@ -47,7 +94,11 @@ pub enum RunState {
         menu_selection: gui::MainMenuSelection,
     },
     SaveGame,
+    LevelUp {
+        selection: usize,
+    },
     GameOver,
+    Victory,
 }
 
 pub struct State {
@@ -63,6 +114,12 @@ impl State {
         let mut visiblity = VisibilitySystem {};
         visiblity.run_now(&self.world);
 
+        let mut lighting = LightingSystem {};
+        lighting.run_now(&self.world);
+
+        let mut memory = MemorySystem {};
+        memory.run_now(&self.world);
+
         let mut monster_ai = MonsterAI {};
         monster_ai.run_now(&self.world);
 
@@ -75,24 +132,47 @@ impl State {
         let mut melee_combat = MeleeCombatSystem {};
         melee_combat.run_now(&self.world);
 
-        let mut damage = DamageSystem {};
-        damage.run_now(&self.world);
-
         let mut item_collection = ItemColecctionSystem {};
         item_collection.run_now(&self.world);
 
         let mut item_drop = ItemDropSystem {};
         item_drop.run_now(&self.world);
 
+        let mut spell_cast = SpellCastSystem {};
+        spell_cast.run_now(&self.world);
+
         let mut item_use = ItemUseSystem {};
         item_use.run_now(&self.world);
 
         let mut item_remove = ItemRemoveSystem {};
         item_remove.run_now(&self.world);
 
+        let mut throws = ThrowSystem {};
+        throws.run_now(&self.world);
+
+        let mut noises = NoiseSystem {};
+        noises.run_now(&self.world);
+
+        let mut encumbrance = EncumbranceSystem {};
+        encumbrance.run_now(&self.world);
+
         let mut hunger = HungerSystem {};
         hunger.run_now(&self.world);
 
+        let mut mana_regen = ManaRegenSystem {};
+        mana_regen.run_now(&self.world);
+
+        let mut wanderers = WanderingMonsterSystem {};
+        wanderers.run_now(&self.world);
+
+        let mut statuses = StatusEffectSystem {};
+        statuses.run_now(&self.world);
+
+        effects::run_effects_queue(&mut self.world);
+
+        let mut damage = DamageSystem {};
+        damage.run_now(&self.world);
+
         let mut particles = ParticleSpawnSystem {};
         particles.run_now(&self.world);
 
@@ -107,11 +187,12 @@ impl State {
         let backpack = self.world.read_storage::<InBackpack>();
         let player_entity = self.world.fetch::<Entity>();
         let equipped = self.world.read_storage::<Equipped>();
+        let spell_templates = self.world.read_storage::<SpellTemplate>();
 
         for entity in entities.join() {
             let mut should_delete = true;
 
-            if let Some(_) = player.get(entity) {
+            if player.get(entity).is_some() || spell_templates.get(entity).is_some() {
                 should_delete = false;
             }
 
@@ -174,9 +255,24 @@ impl State {
             let mut worldmap_resource = self.world.write_resource::<Map>();
             player_start = map_builder.get_starting_position();
             *worldmap_resource = map_builder.get_map();
+            let start_idx = worldmap_resource.xy_idx(player_start.x, player_start.y);
+            worldmap_resource.tiles[start_idx] = TileType::UpStairs;
+            worldmap_resource.dark = lighting::roll_dark_level(
+                &mut self.world.write_resource::<RandomNumberGenerator>(),
+                depth,
+            );
+            if worldmap_resource.dark {
+                self.world
+                    .write_resource::<GameLog>()
+                    .entries
+                    .push("It is pitch dark here.".to_string());
+            }
         }
 
         map_builder.spawn_entities(&mut self.world);
+        noise::settle_sleepers(&mut self.world);
+        wandering::reset_level_timer(&mut self.world);
+        *self.world.write_resource::<RoundState>() = RoundState::new();
 
         let (player_x, player_y) = (player_start.x, player_start.y);
         let mut player_pos = self.world.write_resource::<Point>();
@@ -195,6 +291,63 @@ impl State {
         }
     }
 
+    fn goto_previous_level(&mut self) {
+        for target in self.entities_to_remove_on_level_change() {
+            self.world
+                .delete_entity(target)
+                .expect("Unable to delete entity");
+        }
+
+        let current_depth = self.world.fetch::<Map>().depth;
+        self.generate_world_map(current_depth - 1);
+
+        // Arrive on the way back down rather than at the level's entrance.
+        let stairs = {
+            let map = self.world.fetch::<Map>();
+            map.tiles
+                .iter()
+                .position(|tile| *tile == TileType::DownStairs)
+                .map(|idx| (idx as i32 % map.width, idx as i32 / map.width))
+        };
+        let player_entity = *self.world.fetch::<Entity>();
+        if let Some((x, y)) = stairs {
+            if let Some(pos) = self
+                .world
+                .write_storage::<Position>()
+                .get_mut(player_entity)
+            {
+                pos.x = x;
+                pos.y = y;
+            }
+            *self.world.write_resource::<Point>() = Point::new(x, y);
+        }
+
+        self.world
+            .fetch_mut::<GameLog>()
+            .entries
+            .push("You climb back up the stairs.".to_string());
+    }
+
+    fn player_can_act(&self) -> bool {
+        let player_entity = self.world.fetch::<Entity>();
+        let status_effects = self.world.read_storage::<StatusEffects>();
+        status_effects::can_act(status_effects.get(*player_entity))
+    }
+
+    /// Whether a hasted player acts again before the monsters, at most once per round.
+    fn take_extra_turn(&self) -> bool {
+        let player_entity = self.world.fetch::<Entity>();
+        let status_effects = self.world.read_storage::<StatusEffects>();
+        let mut round = self.world.write_resource::<RoundState>();
+        if round.extra_turn_taken
+            || !status_effects::has_extra_turn(status_effects.get(*player_entity))
+        {
+            return false;
+        }
+        round.extra_turn_taken = true;
+        true
+    }
+
     fn game_over_cleanup(&mut self) {
         let mut to_delete = Vec::new();
         for entity in self.world.entities().join() {
@@ -205,10 +358,13 @@ impl State {
         }
 
         {
+            *self.world.write_resource::<ItemNameTable>() = ItemNameTable::new();
+            *self.world.write_resource::<SlainUniques>() = SlainUniques::new();
             let player_entity = spawner::player(&mut self.world, 0, 0);
             let mut player_entity_writer = self.world.write_resource::<Entity>();
             *player_entity_writer = player_entity;
         }
+        spawner::spells(&mut self.world);
 
         self.generate_world_map(1);
     }
@@ -223,7 +379,9 @@ impl GameState for State {
 
         match new_run_state {
             RunState::MainMenu { .. } => {}
-            RunState::GameOver { .. } => {}
+            RunState::CharacterCreation { .. } => {}
+            RunState::GameOver => {}
+            RunState::Victory => {}
             _ => {
                 let map = self.world.fetch::<Map>();
                 draw_map(&map, ctx);
@@ -232,14 +390,15 @@ impl GameState for State {
                     let positions = self.world.read_storage::<Position>();
                     let renderables = self.world.read_storage::<Renderable>();
                     let hidden = self.world.read_storage::<Hidden>();
+                    let player_pos = *self.world.fetch::<Point>();
+                    memory::draw_memories(&map, player_pos, ctx);
 
                     let mut data = (&positions, &renderables, !&hidden)
                         .join()
                         .collect::<Vec<_>>();
-                    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
+                    data.sort_by_key(|&a| -a.1.render_order);
                     for (pos, render, _hidden) in data.iter() {
-                        let idx = map.xy_idx(pos.x, pos.y);
-                        if map.visible_tiles[idx] {
+                        if lighting::can_see_at(&map, player_pos, pos.x, pos.y) {
                             ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph);
                         }
                     }
@@ -257,7 +416,11 @@ impl GameState for State {
             }
             RunState::AwaitingInput => {
                 self.run_systems();
-                new_run_state = player_input(&mut self.world, ctx);
+                if self.player_can_act() {
+                    new_run_state = player_input(&mut self.world, ctx);
+                } else {
+                    new_run_state = RunState::PlayerTurn;
+                }
             }
             RunState::PlayerTurn => {
                 self.run_systems();
@@ -266,24 +429,51 @@ impl GameState for State {
                     RunState::MagicMapReveal { .. } => {
                         new_run_state = RunState::MagicMapReveal { row: 0 }
                     }
-                    _ => new_run_state = RunState::MonsterTurn,
+                    RunState::NextLevel => new_run_state = RunState::NextLevel,
+                    _ => {
+                        if leveling::can_level_up(&self.world) {
+                            new_run_state = RunState::LevelUp { selection: 0 };
+                        } else if self.take_extra_turn() {
+                            new_run_state = RunState::AwaitingInput;
+                        } else {
+                            new_run_state = RunState::MonsterTurn;
+                        }
+                    }
                 }
             }
             RunState::MonsterTurn => {
                 self.run_systems();
                 self.world.maintain();
-                new_run_state = RunState::AwaitingInput;
+                self.world.write_resource::<RoundState>().extra_turn_taken = false;
+                new_run_state = match *self.world.fetch::<RunState>() {
+                    RunState::NextLevel => RunState::NextLevel,
+                    _ => RunState::AwaitingInput,
+                };
             }
-            RunState::ShowInventory => {
-                let (item_menu_result, entity) = gui::show_inventory(self, ctx);
+            RunState::ShowInventory { menu } => {
+                let (item_menu_result, entity, menu) = gui::show_inventory(self, ctx, menu);
                 match item_menu_result {
                     gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowInventory { menu }
+                    }
                     gui::ItemMenuResult::Selected => {
                         let item_entity = entity.unwrap();
                         let is_ranged = self.world.read_storage::<Ranged>();
                         let is_item_ranged = is_ranged.get(item_entity);
-                        if let Some(is_item_ranged) = is_item_ranged {
+                        let identifies = self.world.read_storage::<Identifies>();
+                        let recharges = self.world.read_storage::<Recharges>();
+                        if identifies.get(item_entity).is_some() {
+                            new_run_state = RunState::ShowIdentify {
+                                scroll: item_entity,
+                                menu: gui::ItemMenuState::default(),
+                            }
+                        } else if recharges.get(item_entity).is_some() {
+                            new_run_state = RunState::ShowRecharge {
+                                scroll: item_entity,
+                                menu: gui::ItemMenuState::default(),
+                            }
+                        } else if let Some(is_item_ranged) = is_item_ranged {
                             new_run_state = RunState::ShowTargeting {
                                 range: is_item_ranged.range,
                                 item: item_entity,
@@ -304,13 +494,15 @@ impl GameState for State {
                     }
                 }
             }
-            RunState::ShowDropItem => {
-                let result = gui::show_drop_item(self, ctx);
-                match result.0 {
+            RunState::ShowDropItem { menu } => {
+                let (result, item_entity, menu) = gui::show_drop_item(self, ctx, menu);
+                match result {
                     gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowDropItem { menu }
+                    }
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
+                        let item_entity = item_entity.unwrap();
                         let mut intent = self.world.write_storage::<WantsToDropItem>();
                         intent
                             .insert(
@@ -322,22 +514,47 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowEquipment => {
+                if gui::show_equipment(self, ctx) == gui::ItemMenuResult::Cancel {
+                    new_run_state = RunState::AwaitingInput;
+                }
+            }
             RunState::ShowTargeting { range, item } => {
                 let (result, point) = gui::ranged_target(self, ctx, range);
                 match result {
                     gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let mut intent = self.world.write_storage::<WantsToUseItem>();
-                        intent
-                            .insert(
-                                *self.world.fetch::<Entity>(),
-                                WantsToUseItem {
-                                    item,
-                                    target: point,
-                                },
-                            )
-                            .expect("Unable to insert intent");
+                        let player_entity = *self.world.fetch::<Entity>();
+                        if self
+                            .world
+                            .read_storage::<SpellTemplate>()
+                            .get(item)
+                            .is_some()
+                        {
+                            self.world
+                                .write_storage::<WantsToCastSpell>()
+                                .insert(
+                                    player_entity,
+                                    WantsToCastSpell {
+                                        spell: item,
+                                        target: point,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                        } else {
+                            self.world
+                                .write_storage::<WantsToUseItem>()
+                                .insert(
+                                    player_entity,
+                                    WantsToUseItem {
+                                        item,
+                                        target: point,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                        }
+                        new_run_state = RunState::PlayerTurn;
                     }
                 }
             }
@@ -345,6 +562,14 @@ impl GameState for State {
                 self.goto_next_level();
                 new_run_state = RunState::PreRun;
             }
+            RunState::PreviousLevel => {
+                if self.world.fetch::<Map>().depth == 1 {
+                    new_run_state = RunState::Victory;
+                } else {
+                    self.goto_previous_level();
+                    new_run_state = RunState::PreRun;
+                }
+            }
             RunState::MainMenu { .. } => {
                 let result = gui::main_menu(self, ctx);
                 match result {
@@ -354,7 +579,14 @@ impl GameState for State {
                         }
                     }
                     gui::MainMenuResult::Selected { selected } => match selected {
-                        gui::MainMenuSelection::NewGame => new_run_state = RunState::PreRun,
+                        gui::MainMenuSelection::NewGame => {
+                            *self.world.write_resource::<CharacterBuilder>() =
+                                CharacterBuilder::new();
+                            new_run_state = RunState::CharacterCreation {
+                                step: gui::CreationStep::Name,
+                                selection: 0,
+                            }
+                        }
                         gui::MainMenuSelection::LoadGame => {
                             saveload::load_game(&mut self.world);
                             new_run_state = RunState::AwaitingInput;
@@ -366,11 +598,120 @@ impl GameState for State {
                     },
                 }
             }
-            RunState::ShowRemoveItem => {
-                let (result, item_entity) = gui::remove_item_menu(self, ctx);
+            RunState::CharacterCreation { step, selection } => {
+                match gui::character_creation(self, ctx, step, selection) {
+                    gui::CharacterCreationResult::NoSelection { step, selection } => {
+                        new_run_state = RunState::CharacterCreation { step, selection }
+                    }
+                    gui::CharacterCreationResult::Cancel => {
+                        new_run_state = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        }
+                    }
+                    gui::CharacterCreationResult::Completed => {
+                        let builder = std::mem::replace(
+                            &mut *self.world.write_resource::<CharacterBuilder>(),
+                            CharacterBuilder::new(),
+                        );
+                        builder.finish(&mut self.world);
+                        new_run_state = RunState::PreRun;
+                    }
+                }
+            }
+            RunState::ShowIdentify { scroll, menu } => {
+                let (result, item_entity, menu) = gui::show_identify(self, ctx, menu);
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowIdentify { scroll, menu }
+                    }
+                    gui::ItemMenuResult::Selected => {
+                        identification::identify_with_scroll(
+                            &mut self.world,
+                            scroll,
+                            item_entity.unwrap(),
+                        );
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowVendor { vendor, mode, menu } => {
+                let (result, item_entity, mode, menu) =
+                    gui::show_vendor(self, ctx, vendor, mode, menu);
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => match mode {
+                        gui::VendorMode::Buy => {
+                            vendors::buy(&mut self.world, vendor, item_entity.unwrap())
+                        }
+                        gui::VendorMode::Sell => {
+                            vendors::sell(&mut self.world, vendor, item_entity.unwrap())
+                        }
+                    },
+                }
+                if result != gui::ItemMenuResult::Cancel {
+                    new_run_state = RunState::ShowVendor { vendor, mode, menu };
+                }
+            }
+            RunState::ShowThrowItem { menu } => {
+                let (result, item_entity, menu) = gui::show_throw_item(self, ctx, menu);
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowThrowItem { menu }
+                    }
+                    gui::ItemMenuResult::Selected => {
+                        new_run_state = RunState::ShowThrowTarget {
+                            item: item_entity.unwrap(),
+                        }
+                    }
+                }
+            }
+            RunState::ShowThrowTarget { item } => {
+                let (result, point) = gui::ranged_target(self, ctx, throwing::THROW_RANGE);
                 match result {
                     gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        self.world
+                            .write_storage::<WantsToThrowItem>()
+                            .insert(
+                                *self.world.fetch::<Entity>(),
+                                WantsToThrowItem {
+                                    item,
+                                    target: point.unwrap(),
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRecharge { scroll, menu } => {
+                let (result, item_entity, menu) = gui::show_recharge(self, ctx, menu);
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowRecharge { scroll, menu }
+                    }
+                    gui::ItemMenuResult::Selected => {
+                        charges::recharge_with_scroll(
+                            &mut self.world,
+                            scroll,
+                            item_entity.unwrap(),
+                        );
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRemoveItem { menu } => {
+                let (result, item_entity, menu) = gui::remove_item_menu(self, ctx, menu);
+                match result {
+                    gui::ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowRemoveItem { menu }
+                    }
                     gui::ItemMenuResult::Selected => {
                         let mut intent = self.world.write_storage::<WantsToRemoveItem>();
                         intent
@@ -421,6 +762,21 @@ impl GameState for State {
                     menu_selection: gui::MainMenuSelection::Quit,
                 }
             }
+            RunState::LevelUp { selection } => match gui::level_up_menu(self, ctx, selection) {
+                gui::LevelUpResult::NoSelection { selection } => {
+                    new_run_state = RunState::LevelUp { selection }
+                }
+                gui::LevelUpResult::Selected { selection } => {
+                    leveling::level_up(&mut self.world, selection);
+                    if leveling::can_level_up(&self.world) {
+                        new_run_state = RunState::LevelUp { selection: 0 };
+                    } else if self.take_extra_turn() {
+                        new_run_state = RunState::AwaitingInput;
+                    } else {
+                        new_run_state = RunState::MonsterTurn;
+                    }
+                }
+            },
             RunState::GameOver => match gui::game_over(ctx) {
                 gui::GameOverResult::NoSelection => {}
                 gui::GameOverResult::QuitToMenu => {
@@ -430,6 +786,15 @@ impl GameState for State {
                     }
                 }
             },
+            RunState::Victory => match gui::victory(ctx) {
+                gui::GameOverResult::NoSelection => {}
+                gui::GameOverResult::QuitToMenu => {
+                    self.game_over_cleanup();
+                    new_run_state = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::NewGame,
+                    }
+                }
+            },
         };
 
         {
@@ -0,0 +1,341 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::{
+    components::{DamageType, StatusEffectType, StatusEffects, Viewshed},
+    effects::{EffectType, EffectsQueue, Targets},
+    gamelog::GameLog,
+    state::RunState,
+};
+
+/// Haste grants at most one extra turn per round; this remembers whether the player has
+/// already taken theirs.
+pub struct RoundState {
+    pub extra_turn_taken: bool,
+}
+
+impl RoundState {
+    pub fn new() -> RoundState {
+        RoundState {
+            extra_turn_taken: false,
+        }
+    }
+}
+
+/// Counts every status down once per round, at the end of the monsters' turn, so an entity
+/// that acts more than once a round does not burn through its statuses faster.
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, StatusEffects>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, EffectsQueue>,
+        WriteStorage<'a, Viewshed>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut status_effects,
+            player_entity,
+            runstate,
+            mut effects,
+            mut viewsheds,
+            mut log,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, statuses) in (&entities, &mut status_effects).join() {
+            for status in statuses.effects.iter_mut() {
+                match status.effect {
+                    StatusEffectType::Poison => {
+                        if entity == *player_entity {
+                            log.entries.push(format!(
+                                "The poison burns! You suffer {} hp damage.",
+                                status.magnitude
+                            ));
+                        }
+                        effects.add_effect(
+                            None,
+                            EffectType::Damage {
+                                amount: status.magnitude,
+                                damage_type: DamageType::Poison,
+                            },
+                            Targets::Single { target: entity },
+                        );
+                    }
+                    StatusEffectType::Regeneration => {
+                        effects.add_effect(
+                            None,
+                            EffectType::Healing {
+                                amount: status.magnitude,
+                            },
+                            Targets::Single { target: entity },
+                        );
+                    }
+                    StatusEffectType::Confusion => {
+                        effects.add_effect(
+                            None,
+                            EffectType::Particle {
+                                glyph: rltk::to_cp437('?'),
+                                fg: RGB::named(rltk::MAGENTA),
+                                bg: RGB::named(rltk::BLACK),
+                                lifetime_ms: 200.0,
+                            },
+                            Targets::Single { target: entity },
+                        );
+                    }
+                    StatusEffectType::Blindness => {
+                        if let Some(viewshed) = viewsheds.get_mut(entity) {
+                            viewshed.dirty = true;
+                        }
+                    }
+                    _ => {}
+                }
+
+                status.turns -= 1;
+                if status.turns < 1 {
+                    if entity == *player_entity {
+                        log.entries
+                            .push(format!("You are no longer {}.", adjective(status.effect)));
+                    }
+                    if status.effect == StatusEffectType::Blindness {
+                        if let Some(viewshed) = viewsheds.get_mut(entity) {
+                            viewshed.dirty = true;
+                        }
+                    }
+                }
+            }
+
+            statuses.effects.retain(|status| status.turns > 0);
+        }
+    }
+}
+
+pub fn has_status(statuses: Option<&StatusEffects>, effect: StatusEffectType) -> bool {
+    statuses.is_some_and(|statuses| statuses.has(effect))
+}
+
+/// Whether an entity gets to act this turn. Paralysed entities never act, and slowed
+/// entities only act on every other turn.
+pub fn can_act(statuses: Option<&StatusEffects>) -> bool {
+    match statuses {
+        None => true,
+        Some(statuses) => {
+            if statuses.has(StatusEffectType::Paralysis) {
+                return false;
+            }
+            match statuses.get(StatusEffectType::Slow) {
+                Some(slow) => slow.turns % 2 == 1,
+                None => true,
+            }
+        }
+    }
+}
+
+/// Hasted entities act a second time on every other turn.
+pub fn has_extra_turn(statuses: Option<&StatusEffects>) -> bool {
+    match statuses.and_then(|statuses| statuses.get(StatusEffectType::Haste)) {
+        Some(haste) => haste.turns % 2 == 0,
+        None => false,
+    }
+}
+
+pub fn adjective(effect: StatusEffectType) -> &'static str {
+    match effect {
+        StatusEffectType::Poison => "poisoned",
+        StatusEffectType::Slow => "slowed",
+        StatusEffectType::Haste => "hasted",
+        StatusEffectType::Paralysis => "paralysed",
+        StatusEffectType::Regeneration => "regenerating",
+        StatusEffectType::Blindness => "blind",
+        StatusEffectType::Confusion => "confused",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::{
+            Alerted, Asleep, CombatStats, Monster, Position, StatusEffect, SuffersDamage,
+        },
+        effects,
+        map::Map,
+    };
+
+    fn test_world() -> (World, Entity) {
+        let mut world = World::new();
+        world.register::<StatusEffects>();
+        world.register::<Viewshed>();
+        world.register::<CombatStats>();
+        world.register::<SuffersDamage>();
+        world.register::<Position>();
+        world.register::<Monster>();
+        world.register::<Asleep>();
+        world.register::<Alerted>();
+        world.insert(Map::new(1));
+        world.insert(EffectsQueue::new());
+        world.insert(GameLog {
+            entries: Vec::new(),
+        });
+        world.insert(RunState::MonsterTurn);
+        let player = world.create_entity().build();
+        world.insert(player);
+        let monster = world
+            .create_entity()
+            .with(CombatStats { max_hp: 10, hp: 5 })
+            .with(Viewshed {
+                visible_tiles: Vec::new(),
+                range: 8,
+                dirty: false,
+            })
+            .build();
+        (world, monster)
+    }
+
+    fn afflict(world: &mut World, target: Entity, effect: StatusEffectType, turns: i32) {
+        world
+            .write_storage::<StatusEffects>()
+            .insert(
+                target,
+                StatusEffects {
+                    effects: vec![StatusEffect {
+                        effect,
+                        turns,
+                        magnitude: 2,
+                    }],
+                },
+            )
+            .unwrap();
+    }
+
+    fn tick(world: &mut World) {
+        let mut system = StatusEffectSystem {};
+        system.run_now(world);
+        effects::run_effects_queue(world);
+    }
+
+    fn with(effect: StatusEffectType, turns: i32) -> StatusEffects {
+        let mut statuses = StatusEffects::default();
+        statuses.add(effect, turns);
+        statuses
+    }
+
+    #[test]
+    fn poison_deals_its_magnitude_each_turn() {
+        let (mut world, monster) = test_world();
+        afflict(&mut world, monster, StatusEffectType::Poison, 3);
+        tick(&mut world);
+        let damage = world.read_storage::<SuffersDamage>();
+        assert_eq!(
+            damage.get(monster).unwrap().amount,
+            vec![(2, DamageType::Poison)]
+        );
+    }
+
+    #[test]
+    fn regeneration_heals_its_magnitude_each_turn() {
+        let (mut world, monster) = test_world();
+        afflict(&mut world, monster, StatusEffectType::Regeneration, 3);
+        tick(&mut world);
+        assert_eq!(
+            world.read_storage::<CombatStats>().get(monster).unwrap().hp,
+            7
+        );
+    }
+
+    #[test]
+    fn blindness_refreshes_the_viewshed_while_it_lasts() {
+        let (mut world, monster) = test_world();
+        afflict(&mut world, monster, StatusEffectType::Blindness, 3);
+        tick(&mut world);
+        assert!(world.read_storage::<Viewshed>().get(monster).unwrap().dirty);
+    }
+
+    #[test]
+    fn statuses_expire_when_their_turns_run_out() {
+        let (mut world, monster) = test_world();
+        afflict(&mut world, monster, StatusEffectType::Confusion, 1);
+        tick(&mut world);
+        let statuses = world.read_storage::<StatusEffects>();
+        assert!(!has_status(
+            statuses.get(monster),
+            StatusEffectType::Confusion
+        ));
+    }
+
+    #[test]
+    fn statuses_only_tick_at_the_end_of_the_round() {
+        let (mut world, monster) = test_world();
+        let player = *world.fetch::<Entity>();
+        afflict(&mut world, monster, StatusEffectType::Confusion, 1);
+        afflict(&mut world, player, StatusEffectType::Haste, 2);
+
+        // A hasted player's two turns leave every status untouched...
+        *world.write_resource::<RunState>() = RunState::PlayerTurn;
+        tick(&mut world);
+        tick(&mut world);
+        {
+            let statuses = world.read_storage::<StatusEffects>();
+            assert!(has_status(
+                statuses.get(monster),
+                StatusEffectType::Confusion
+            ));
+            assert_eq!(
+                statuses
+                    .get(player)
+                    .unwrap()
+                    .get(StatusEffectType::Haste)
+                    .unwrap()
+                    .turns,
+                2
+            );
+        }
+
+        // ...and the monsters' turn counts everyone's down once, the player's included.
+        *world.write_resource::<RunState>() = RunState::MonsterTurn;
+        tick(&mut world);
+        let statuses = world.read_storage::<StatusEffects>();
+        assert!(!has_status(
+            statuses.get(monster),
+            StatusEffectType::Confusion
+        ));
+        assert_eq!(
+            statuses
+                .get(player)
+                .unwrap()
+                .get(StatusEffectType::Haste)
+                .unwrap()
+                .turns,
+            1
+        );
+    }
+
+    #[test]
+    fn paralysis_stops_all_actions() {
+        assert!(can_act(None));
+        assert!(!can_act(Some(&with(StatusEffectType::Paralysis, 3))));
+    }
+
+    #[test]
+    fn slow_acts_on_every_other_turn() {
+        assert!(can_act(Some(&with(StatusEffectType::Slow, 3))));
+        assert!(!can_act(Some(&with(StatusEffectType::Slow, 2))));
+    }
+
+    #[test]
+    fn haste_grants_an_extra_turn_every_other_turn() {
+        assert!(!has_extra_turn(None));
+        assert!(has_extra_turn(Some(&with(StatusEffectType::Haste, 2))));
+        assert!(!has_extra_turn(Some(&with(StatusEffectType::Haste, 3))));
+    }
+}
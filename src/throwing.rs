@@ -0,0 +1,290 @@
+use rltk::{LineAlg, Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{
+        Attributes, CombatStats, DefenseBonus, Equipped, InBackpack, InflictsDamage,
+        InflictsStatus, Item, ItemCategory, MeleeWeapon, Name, Position, ProvidesHealing,
+        Resistances, Skills, Stackable, Throwable, WantsToThrowItem,
+    },
+    damage,
+    effects::{EffectType, EffectsQueue, Targets},
+    gamelog::GameLog,
+    identification::ItemNameTable,
+    inventory,
+    map::{Map, TileType},
+    melee_combat::{roll_attack, roll_damage, AttackRoll},
+    noise::{NoiseQueue, IMPACT_VOLUME},
+};
+
+pub const THROW_RANGE: i32 = 6;
+/// Radius of the splash when a thrown potion shatters.
+const SHATTER_RADIUS: i32 = 1;
+
+pub struct ThrowSystem {}
+
+impl<'a> System<'a> for ThrowSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToThrowItem>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Throwable>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Stackable>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, EffectsQueue>,
+        WriteExpect<'a, ItemNameTable>,
+        Read<'a, LazyUpdate>,
+        WriteExpect<'a, NoiseQueue>,
+        ReadStorage<'a, Resistances>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            map,
+            entities,
+            mut wants_throw,
+            names,
+            items,
+            combat_stats,
+            attributes,
+            skills,
+            equipped,
+            defense_bonuses,
+            throwable,
+            melee_weapons,
+            healing,
+            inflicts_damage,
+            inflicts_status,
+            mut positions,
+            mut backpack,
+            mut stackables,
+            mut rng,
+            mut effects,
+            mut item_names,
+            lazy,
+            mut noise,
+            resistances,
+        ) = data;
+
+        for (entity, throw) in (&entities, &wants_throw).join() {
+            let start = match positions.get(entity) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y),
+            };
+
+            let mut landing = start;
+            let mut victim = None;
+            for step in rltk::line2d(LineAlg::Bresenham, start, throw.target)
+                .into_iter()
+                .skip(1)
+            {
+                let idx = map.xy_idx(step.x, step.y);
+                if map.tiles[idx] == TileType::Wall {
+                    break;
+                }
+                landing = step;
+                victim = map.tile_content[idx]
+                    .iter()
+                    .find(|potential| combat_stats.get(**potential).is_some())
+                    .copied();
+                if victim.is_some() {
+                    break;
+                }
+            }
+
+            noise.make(landing.x, landing.y, IMPACT_VOLUME);
+            let is_player = entity == *player_entity;
+            let item_name = item_names.display_name(&names.get(throw.item).unwrap().name);
+            let is_potion = items
+                .get(throw.item)
+                .is_some_and(|item| item.category == ItemCategory::Potion);
+
+            if is_potion {
+                let true_name = &names.get(throw.item).unwrap().name;
+                if let Some(previous) = item_names.identify(true_name) {
+                    if is_player {
+                        gamelog.entries.push(format!(
+                            "The {} shatters; it was a {}!",
+                            previous, true_name
+                        ));
+                    }
+                } else if is_player {
+                    gamelog.entries.push(format!("The {} shatters!", item_name));
+                }
+
+                let mut splash = rltk::field_of_view(landing, SHATTER_RADIUS, &*map);
+                splash
+                    .retain(|p| p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1);
+                let tiles: Vec<usize> = splash.iter().map(|p| map.xy_idx(p.x, p.y)).collect();
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::Particle {
+                        glyph: rltk::to_cp437('░'),
+                        fg: RGB::named(rltk::MAGENTA),
+                        bg: RGB::named(rltk::BLACK),
+                        lifetime_ms: 200.0,
+                    },
+                    Targets::Area {
+                        tiles: tiles.clone(),
+                    },
+                );
+                let targets = Targets::Area { tiles };
+                if let Some(healer) = healing.get(throw.item) {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Healing {
+                            amount: healer.heal_amount,
+                        },
+                        targets.clone(),
+                    );
+                }
+                if let Some(damage) = inflicts_damage.get(throw.item) {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Damage {
+                            amount: damage.damage,
+                            damage_type: damage.damage_type,
+                        },
+                        targets.clone(),
+                    );
+                }
+                if let Some(status) = inflicts_status.get(throw.item) {
+                    effects.add_effect(
+                        Some(entity),
+                        EffectType::Status {
+                            effect: status.effect,
+                            turns: status.turns,
+                        },
+                        targets,
+                    );
+                }
+
+                match stackables.get_mut(throw.item) {
+                    Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+                    _ => entities.delete(throw.item).expect("Delete failed"),
+                }
+                continue;
+            }
+
+            let weapon = melee_weapons
+                .get(throw.item)
+                .filter(|_weapon| throwable.get(throw.item).is_some());
+            match (victim, weapon) {
+                (Some(victim), Some(weapon)) => {
+                    let thrower_attributes = attributes.get(entity).cloned().unwrap_or_default();
+                    let to_hit = attributes::ranged_to_hit(&thrower_attributes) + weapon.hit_bonus;
+                    let defense: i32 = (&defense_bonuses, &equipped)
+                        .join()
+                        .filter(|(_bonus, worn)| worn.owner == victim)
+                        .map(|(bonus, _worn)| bonus.defense)
+                        .sum();
+                    let armor_class = attributes::armor_class(
+                        &attributes.get(victim).cloned().unwrap_or_default(),
+                        &skills.get(victim).cloned().unwrap_or_default(),
+                    ) + defense;
+                    let victim_name = &names.get(victim).unwrap().name;
+
+                    match roll_attack(&mut rng, to_hit, armor_class) {
+                        AttackRoll::Fumble | AttackRoll::Miss => {
+                            if is_player {
+                                gamelog
+                                    .entries
+                                    .push(format!("The {} misses {}.", item_name, victim_name));
+                            }
+                        }
+                        attack => {
+                            let damage = roll_damage(
+                                &mut rng,
+                                weapon.damage,
+                                0,
+                                attack == AttackRoll::Critical,
+                            );
+                            effects.add_effect(
+                                Some(entity),
+                                EffectType::Damage {
+                                    amount: damage,
+                                    damage_type: weapon.damage_type,
+                                },
+                                Targets::Single { target: victim },
+                            );
+                            if is_player {
+                                let dealt = damage::resolved_damage(
+                                    victim,
+                                    damage,
+                                    weapon.damage_type,
+                                    &resistances,
+                                    &equipped,
+                                );
+                                gamelog.entries.push(format!(
+                                    "The {} hits {}, for {} hp.",
+                                    item_name, victim_name, dealt
+                                ));
+                            }
+                        }
+                    }
+                }
+                (Some(victim), None) => {
+                    if is_player {
+                        gamelog.entries.push(format!(
+                            "The {} bounces harmlessly off {}.",
+                            item_name,
+                            names.get(victim).unwrap().name
+                        ));
+                    }
+                }
+                (None, _) => {
+                    if is_player {
+                        gamelog
+                            .entries
+                            .push(format!("The {} clatters to the floor.", item_name));
+                    }
+                }
+            }
+
+            let landed_at = Position {
+                x: landing.x,
+                y: landing.y,
+            };
+            match stackables.get_mut(throw.item) {
+                Some(stack) if stack.quantity > 1 => {
+                    stack.quantity -= 1;
+                    let item = throw.item;
+                    lazy.exec_mut(move |world| {
+                        let single = inventory::split_stack(world, item);
+                        world
+                            .write_storage::<Position>()
+                            .insert(single, landed_at)
+                            .expect("Unable to insert position");
+                    });
+                }
+                _ => {
+                    backpack.remove(throw.item);
+                    positions
+                        .insert(throw.item, landed_at)
+                        .expect("Unable to insert position");
+                }
+            }
+        }
+
+        wants_throw.clear();
+    }
+}
@@ -0,0 +1,122 @@
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use crate::{
+    attributes,
+    components::{Attributes, EntryTrigger, Hidden, Name, Position, Skills},
+    gamelog::GameLog,
+    state::RunState,
+};
+
+/// Searching turns up hidden traps this many tiles away.
+const SEARCH_RADIUS: f32 = 3.0;
+/// Odds (out of 20) of a search finding a trap before the detection bonus.
+const SEARCH_CHANCE: i32 = 8;
+const DISARM_DIFFICULTY: i32 = 12;
+
+fn player_skill(world: &World, skill: fn(&Attributes, &Skills) -> i32) -> i32 {
+    let player_entity = *world.fetch::<Entity>();
+    let attributes = world
+        .read_storage::<Attributes>()
+        .get(player_entity)
+        .cloned()
+        .unwrap_or_default();
+    let skills = world
+        .read_storage::<Skills>()
+        .get(player_entity)
+        .cloned()
+        .unwrap_or_default();
+    skill(&attributes, &skills)
+}
+
+/// Spends a turn looking closely for traps around the player.
+pub fn search(world: &mut World) -> RunState {
+    let player_pos = *world.fetch::<Point>();
+    let detection = player_skill(world, attributes::trap_detection);
+    let entities = world.entities();
+    let triggers = world.read_storage::<EntryTrigger>();
+    let positions = world.read_storage::<Position>();
+    let names = world.read_storage::<Name>();
+    let mut hidden = world.write_storage::<Hidden>();
+    let mut rng = world.write_resource::<RandomNumberGenerator>();
+    let mut gamelog = world.write_resource::<GameLog>();
+
+    let nearby: Vec<Entity> = (&entities, &triggers, &positions, &hidden)
+        .join()
+        .filter(|(_entity, _trigger, pos, _hidden)| {
+            DistanceAlg::Pythagoras.distance2d(player_pos, Point::new(pos.x, pos.y))
+                <= SEARCH_RADIUS
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+
+    let mut found = false;
+    for trap in nearby {
+        if rng.roll_dice(1, 20) <= SEARCH_CHANCE + detection {
+            if let Some(name) = names.get(trap) {
+                gamelog
+                    .entries
+                    .push(format!("Your search reveals a {}.", name.name));
+            }
+            hidden.remove(trap);
+            found = true;
+        }
+    }
+    if !found {
+        gamelog
+            .entries
+            .push("You search the area but find nothing.".to_string());
+    }
+
+    RunState::PlayerTurn
+}
+
+/// Tries to disarm a revealed trap next to (or under) the player.
+pub fn disarm(world: &mut World) -> RunState {
+    let player_pos = *world.fetch::<Point>();
+    let trap = {
+        let entities = world.entities();
+        let triggers = world.read_storage::<EntryTrigger>();
+        let positions = world.read_storage::<Position>();
+        let hidden = world.read_storage::<Hidden>();
+        (&entities, &triggers, &positions, !&hidden)
+            .join()
+            .find(|(_entity, _trigger, pos, _hidden)| {
+                DistanceAlg::Pythagoras.distance2d(player_pos, Point::new(pos.x, pos.y)) < 1.5
+            })
+            .map(|(entity, ..)| entity)
+    };
+    let trap = match trap {
+        None => {
+            world
+                .write_resource::<GameLog>()
+                .entries
+                .push("There is no trap nearby to disarm.".to_string());
+            return RunState::AwaitingInput;
+        }
+        Some(trap) => trap,
+    };
+
+    let name = world
+        .read_storage::<Name>()
+        .get(trap)
+        .map_or("trap".to_string(), |name| name.name.clone());
+    let bonus = player_skill(world, attributes::disarm_bonus);
+    let roll = world
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 20);
+    if roll + bonus >= DISARM_DIFFICULTY {
+        world
+            .write_resource::<GameLog>()
+            .entries
+            .push(format!("You disarm the {}.", name));
+        world.delete_entity(trap).expect("Unable to delete trap");
+    } else {
+        world
+            .write_resource::<GameLog>()
+            .entries
+            .push(format!("You fail to disarm the {}.", name));
+    }
+
+    RunState::PlayerTurn
+}
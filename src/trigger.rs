@@ -1,15 +1,22 @@
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
 use crate::{
     components::{
-        EntityMoved, EntryTrigger, Hidden, InflictsDamage, Name, Position, SingleActivation,
-        SuffersDamage,
+        Alarm, Alerted, AreaOfEffect, EntityMoved, EntryTrigger, Hidden, InflictsDamage,
+        InflictsStatus, Monster, Name, Position, SingleActivation, Teleports, TrapDoor,
     },
+    damage,
+    effects::{EffectType, EffectsQueue, Targets},
     gamelog::GameLog,
-    map::Map,
-    particles::ParticlesBuilder,
+    map::{Map, TileType},
+    noise::{NoiseQueue, TRAP_VOLUME},
+    state::RunState,
 };
 
+/// Monsters within this many tiles of a sprung alarm come running.
+const ALARM_RADIUS: f32 = 20.0;
+
 pub struct TriggerSystem {}
 
 impl<'a> System<'a> for TriggerSystem {
@@ -20,13 +27,24 @@ impl<'a> System<'a> for TriggerSystem {
         ReadStorage<'a, Position>,
         ReadStorage<'a, EntryTrigger>,
         ReadStorage<'a, InflictsDamage>,
-        WriteStorage<'a, SuffersDamage>,
+        ReadStorage<'a, InflictsStatus>,
         ReadStorage<'a, SingleActivation>,
         WriteStorage<'a, Hidden>,
         ReadStorage<'a, Name>,
         Entities<'a>,
         WriteExpect<'a, GameLog>,
-        WriteExpect<'a, ParticlesBuilder>,
+        WriteExpect<'a, EffectsQueue>,
+        ReadStorage<'a, AreaOfEffect>,
+        ReadStorage<'a, Teleports>,
+        ReadStorage<'a, Alarm>,
+        ReadStorage<'a, TrapDoor>,
+        ReadStorage<'a, Monster>,
+        WriteStorage<'a, Alerted>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RunState>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, NoiseQueue>,
+        Read<'a, LazyUpdate>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,13 +54,24 @@ impl<'a> System<'a> for TriggerSystem {
             position,
             entry_trigger,
             inflicts_damage,
-            mut suffers_damage,
+            inflicts_status,
             single_activations,
             mut hidden,
             names,
             entities,
             mut gamelog,
-            mut particles_builder,
+            mut effects,
+            area_of_effect,
+            teleports,
+            alarms,
+            trap_doors,
+            monsters,
+            mut alerted,
+            player_entity,
+            mut runstate,
+            mut rng,
+            mut noise,
+            lazy,
         ) = data;
 
         let mut remove_entities: Vec<Entity> = Vec::new();
@@ -56,24 +85,110 @@ impl<'a> System<'a> for TriggerSystem {
                             if let Some(name) = names.get(*entity_id) {
                                 gamelog.entries.push(format!("{} triggers!", &name.name));
                             }
+                            noise.make(pos.x, pos.y, TRAP_VOLUME);
+
+                            let targets = match area_of_effect.get(*entity_id) {
+                                None => Targets::Single { target: entity },
+                                Some(area) => {
+                                    let mut blast = rltk::field_of_view(
+                                        Point::new(pos.x, pos.y),
+                                        area.radius,
+                                        &*map,
+                                    );
+                                    blast.retain(|p| {
+                                        p.x > 0
+                                            && p.x < map.width - 1
+                                            && p.y > 0
+                                            && p.y < map.height - 1
+                                    });
+                                    Targets::Area {
+                                        tiles: blast.iter().map(|p| map.xy_idx(p.x, p.y)).collect(),
+                                    }
+                                }
+                            };
 
                             if let Some(damage) = inflicts_damage.get(*entity_id) {
-                                particles_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    rltk::RGB::named(rltk::ORANGE),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('‼'),
-                                    200.0,
+                                effects.add_effect(
+                                    Some(*entity_id),
+                                    EffectType::Damage {
+                                        amount: damage.damage,
+                                        damage_type: damage.damage_type,
+                                    },
+                                    targets.clone(),
                                 );
-                                SuffersDamage::new_damage(
-                                    &mut suffers_damage,
-                                    entity,
-                                    damage.damage,
+                            }
+
+                            if let Some(status) = inflicts_status.get(*entity_id) {
+                                effects.add_effect(
+                                    Some(*entity_id),
+                                    EffectType::Status {
+                                        effect: status.effect,
+                                        turns: status.turns,
+                                    },
+                                    targets,
                                 );
                             }
 
-                            if let Some(_) = single_activations.get(*entity_id) {
+                            if teleports.get(*entity_id).is_some() {
+                                for _try in 0..20 {
+                                    let x = rng.roll_dice(1, map.width - 2);
+                                    let y = rng.roll_dice(1, map.height - 2);
+                                    let destination = map.xy_idx(x, y);
+                                    if map.tiles[destination] == TileType::Floor
+                                        && !map.blocked[destination]
+                                    {
+                                        effects.add_effect(
+                                            Some(*entity_id),
+                                            EffectType::Teleport { x, y },
+                                            Targets::Single { target: entity },
+                                        );
+                                        break;
+                                    }
+                                }
+                            }
+
+                            if alarms.get(*entity_id).is_some() {
+                                gamelog
+                                    .entries
+                                    .push("A shrill alarm echoes through the halls!".to_string());
+                                let origin = Point::new(pos.x, pos.y);
+                                for (monster, _monster, monster_pos) in
+                                    (&entities, &monsters, &position).join()
+                                {
+                                    let distance = DistanceAlg::Pythagoras.distance2d(
+                                        origin,
+                                        Point::new(monster_pos.x, monster_pos.y),
+                                    );
+                                    if distance <= ALARM_RADIUS {
+                                        alerted
+                                            .insert(monster, Alerted::at(pos.x, pos.y))
+                                            .expect("Unable to insert alert");
+                                    }
+                                }
+                            }
+
+                            if trap_doors.get(*entity_id).is_some() {
+                                if entity == *player_entity {
+                                    gamelog
+                                        .entries
+                                        .push("You fall through to the level below!".to_string());
+                                    *runstate = RunState::NextLevel;
+                                } else {
+                                    if let Some(name) = names.get(entity) {
+                                        gamelog
+                                            .entries
+                                            .push(format!("{} falls out of sight!", name.name));
+                                    }
+                                    // Whatever it carried stays behind at the edge of the drop.
+                                    let fallen = entity;
+                                    lazy.exec_mut(move |world| {
+                                        damage::drop_carried(world, fallen);
+                                        world.delete_entity(fallen).expect("Unable to delete");
+                                    });
+                                }
+                            }
+
+                            if single_activations.get(*entity_id).is_some() {
                                 remove_entities.push(*entity_id);
                             }
 
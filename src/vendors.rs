@@ -0,0 +1,156 @@
+use serde::Deserialize;
+use specs::prelude::*;
+
+use crate::{
+    components::{InBackpack, Item, ItemCategory, Name, Stackable, Wallet},
+    gamelog::GameLog,
+    identification::ItemNameTable,
+    inventory,
+};
+
+/// Vendors pay this fraction of an item's value when buying from the player.
+pub const SELL_MARKDOWN: f32 = 0.5;
+
+#[derive(Deserialize)]
+pub struct VendorData {
+    pub name: String,
+    pub categories: Vec<ItemCategory>,
+    pub stock: Vec<StockEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct StockEntry {
+    pub name: String,
+    pub quantity: i32,
+}
+
+fn all_vendors() -> Vec<VendorData> {
+    serde_json::from_str(include_str!("../resources/vendors.json"))
+        .expect("Unable to parse vendor data")
+}
+
+pub fn vendor_data(name: &str) -> Option<VendorData> {
+    all_vendors().into_iter().find(|vendor| vendor.name == name)
+}
+
+pub fn vendor_names() -> Vec<String> {
+    all_vendors()
+        .into_iter()
+        .map(|vendor| vendor.name)
+        .collect()
+}
+
+pub fn buy_price(item: &Item) -> i32 {
+    item.base_value
+}
+
+pub fn sell_price(item: &Item) -> i32 {
+    i32::max(1, (item.base_value as f32 * SELL_MARKDOWN) as i32)
+}
+
+pub fn buy(world: &mut World, vendor: Entity, item: Entity) {
+    let player_entity = *world.fetch::<Entity>();
+    let price = buy_price(world.read_storage::<Item>().get(item).unwrap());
+    let name = display_name(world, item);
+    let gold = world
+        .read_storage::<Wallet>()
+        .get(player_entity)
+        .map_or(0, |wallet| wallet.gold);
+    if gold < price {
+        world
+            .write_resource::<GameLog>()
+            .entries
+            .push(format!("You can't afford the {}.", name));
+        return;
+    }
+
+    if let Some(wallet) = world.write_storage::<Wallet>().get_mut(player_entity) {
+        wallet.gold -= price;
+    }
+    transfer_one(world, item, player_entity);
+    let vendor_name = world
+        .read_storage::<Name>()
+        .get(vendor)
+        .unwrap()
+        .name
+        .clone();
+    world.write_resource::<GameLog>().entries.push(format!(
+        "You buy the {} from the {} for {} gold.",
+        name, vendor_name, price
+    ));
+}
+
+pub fn sell(world: &mut World, vendor: Entity, item: Entity) {
+    let player_entity = *world.fetch::<Entity>();
+    let price = sell_price(world.read_storage::<Item>().get(item).unwrap());
+    let name = display_name(world, item);
+
+    if let Some(wallet) = world.write_storage::<Wallet>().get_mut(player_entity) {
+        wallet.gold += price;
+    }
+    transfer_one(world, item, vendor);
+    world
+        .write_resource::<GameLog>()
+        .entries
+        .push(format!("You sell the {} for {} gold.", name, price));
+}
+
+fn display_name(world: &World, item: Entity) -> String {
+    let names = world.read_storage::<Name>();
+    world
+        .fetch::<ItemNameTable>()
+        .display_name(&names.get(item).unwrap().name)
+}
+
+/// Moves a single unit of `item` into `owner`'s backpack, splitting and merging stacks.
+fn transfer_one(world: &mut World, item: Entity, owner: Entity) {
+    let quantity = world
+        .read_storage::<Stackable>()
+        .get(item)
+        .map_or(1, |stack| stack.quantity);
+    let moved = if quantity > 1 {
+        world
+            .write_storage::<Stackable>()
+            .get_mut(item)
+            .unwrap()
+            .quantity -= 1;
+        inventory::split_stack(world, item)
+    } else {
+        item
+    };
+
+    let existing_stack = {
+        let entities = world.entities();
+        let names = world.read_storage::<Name>();
+        let backpack = world.read_storage::<InBackpack>();
+        let stackables = world.read_storage::<Stackable>();
+        let moved_name = &names.get(moved).unwrap().name;
+        if stackables.get(moved).is_some() {
+            (&entities, &backpack, &names, &stackables)
+                .join()
+                .find(|(entity, carried_by, name, _stack)| {
+                    *entity != moved && carried_by.owner == owner && name.name == *moved_name
+                })
+                .map(|(entity, ..)| entity)
+        } else {
+            None
+        }
+    };
+
+    match existing_stack {
+        Some(stack) => {
+            world
+                .write_storage::<Stackable>()
+                .get_mut(stack)
+                .unwrap()
+                .quantity += 1;
+            world.delete_entity(moved).expect("Delete failed");
+        }
+        None => {
+            world
+                .write_storage::<InBackpack>()
+                .insert(moved, InBackpack { owner })
+                .expect("Unable to insert backpack entry");
+        }
+    }
+}
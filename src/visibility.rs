@@ -2,9 +2,14 @@ use rltk::{field_of_view, Point};
 use specs::prelude::*;
 
 use crate::{
-    components::{Hidden, Name, Player, Position, Viewshed},
+    attributes,
+    components::{
+        Attributes, Hidden, Name, Player, Position, Skills, StatusEffectType, StatusEffects,
+        Viewshed,
+    },
     gamelog::GameLog,
     map::Map,
+    status_effects,
 };
 
 pub struct VisibilitySystem {}
@@ -20,24 +25,48 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Hidden>,
         WriteExpect<'a, rltk::RandomNumberGenerator>,
         WriteExpect<'a, GameLog>,
+        ReadStorage<'a, StatusEffects>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
     );
 
     fn run(
         &mut self,
-        (mut map, entities, mut viewshed, pos, player, names, mut hidden, mut rng, mut gamelog): Self::SystemData,
+        (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            names,
+            mut hidden,
+            mut rng,
+            mut gamelog,
+            status_effects,
+            attributes,
+            skills,
+        ): Self::SystemData,
     ) {
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
                 viewshed.dirty = false;
                 viewshed.visible_tiles.clear();
-                viewshed.visible_tiles =
-                    field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
+                let blind = status_effects::has_status(
+                    status_effects.get(ent),
+                    StatusEffectType::Blindness,
+                );
+                let range = if blind { 1 } else { viewshed.range };
+                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, &*map);
                 viewshed
                     .visible_tiles
                     .retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height);
 
                 let player_ent = player.get(ent);
-                if let Some(_) = player_ent {
+                if player_ent.is_some() {
+                    let detection = attributes::trap_detection(
+                        &attributes.get(ent).cloned().unwrap_or_default(),
+                        &skills.get(ent).cloned().unwrap_or_default(),
+                    );
                     for tile_visible in map.visible_tiles.iter_mut() {
                         *tile_visible = false;
                     }
@@ -47,15 +76,15 @@ impl<'a> System<'a> for VisibilitySystem {
                         map.visible_tiles[idx] = true;
 
                         for tile_content in map.tile_content[idx].iter() {
-                            if let Some(_) = hidden.get(*tile_content) {
-                                if rng.roll_dice(1, 24) == 1 {
-                                    if let Some(name) = names.get(*tile_content) {
-                                        gamelog
-                                            .entries
-                                            .push(format!("You spotted a {}.", &name.name));
-                                    }
-                                    hidden.remove(*tile_content);
+                            if hidden.get(*tile_content).is_some()
+                                && rng.roll_dice(1, 24) <= i32::max(1, 1 + detection)
+                            {
+                                if let Some(name) = names.get(*tile_content) {
+                                    gamelog
+                                        .entries
+                                        .push(format!("You spotted a {}.", &name.name));
                                 }
+                                hidden.remove(*tile_content);
                             }
                         }
                     }
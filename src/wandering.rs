@@ -0,0 +1,80 @@
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use crate::{
+    components::LevelTimer,
+    map::{Map, TileType},
+    spawner,
+    state::RunState,
+};
+
+/// Chance per thousand turns of a wanderer arriving when the player first reaches a level.
+const BASE_CHANCE: i32 = 2;
+/// The chance rises by one per thousand for every this many turns spent on the level.
+const ESCALATION_TURNS: i32 = 50;
+const MAX_CHANCE: i32 = 50;
+/// Wanderers turn up at least this far from the player, as well as out of sight.
+const MIN_DISTANCE: f32 = 10.0;
+
+fn spawn_chance(turns_on_level: i32) -> i32 {
+    i32::min(MAX_CHANCE, BASE_CHANCE + turns_on_level / ESCALATION_TURNS)
+}
+
+pub struct WanderingMonsterSystem {}
+
+impl<'a> System<'a> for WanderingMonsterSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, LevelTimer>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, runstate, map, player_pos, mut rng, mut level_timers, lazy) = data;
+
+        if *runstate != RunState::PlayerTurn {
+            return;
+        }
+        let timer = match level_timers.get_mut(*player_entity) {
+            None => return,
+            Some(timer) => timer,
+        };
+        timer.turns_on_level += 1;
+        if rng.roll_dice(1, 1000) > spawn_chance(timer.turns_on_level) {
+            return;
+        }
+
+        for _try in 0..20 {
+            let x = rng.roll_dice(1, map.width - 2);
+            let y = rng.roll_dice(1, map.height - 2);
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] != TileType::Floor
+                || map.blocked[idx]
+                || map.visible_tiles[idx]
+                || DistanceAlg::Pythagoras.distance2d(*player_pos, Point::new(x, y)) < MIN_DISTANCE
+            {
+                continue;
+            }
+
+            if let Some(name) = spawner::monster_table(map.depth).roll(&mut rng, map.depth) {
+                let depth = map.depth;
+                lazy.exec_mut(move |world| {
+                    spawner::spawn_entity(world, name, x, y, depth);
+                });
+            }
+            return;
+        }
+    }
+}
+
+pub fn reset_level_timer(world: &mut World) {
+    let player_entity = *world.fetch::<Entity>();
+    if let Some(timer) = world.write_storage::<LevelTimer>().get_mut(player_entity) {
+        timer.turns_on_level = 0;
+    }
+}